@@ -1,3 +1,3 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 
-export type ActionError = "credential" | "git" | { "iO": string };
+export type ActionError = "credential" | "git" | { "hook": string } | { "iO": string };
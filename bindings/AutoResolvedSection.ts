@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AutoResolveReason } from "./AutoResolveReason";
+import type { SectionChoice } from "./SectionChoice";
+
+export type AutoResolvedSection = { section: number, reason: AutoResolveReason, choice: SectionChoice, };
@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { BlameCommit } from "./BlameCommit";
+import type { BlameGroup } from "./BlameGroup";
+
+export type Blame = { groups: Array<BlameGroup>, commits: { [key in string]?: BlameCommit }, };
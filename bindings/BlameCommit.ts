@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Commit } from "./Commit";
+import type { DateResult } from "./DateResult";
+
+export type BlameCommit = { id: string, author: string, email: string, date: DateResult, summary: string, boundary: boolean, uncommitted: boolean, commit: Commit | null, };
@@ -1,4 +1,4 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 import type { CFSectionLine } from "./CFSectionLine";
 
-export type CFSection = { left: Array<CFSectionLine>, right: Array<CFSectionLine>, };
+export type CFSection = { left: Array<CFSectionLine>, right: Array<CFSectionLine>, base: Array<CFSectionLine> | null, };
@@ -1,3 +1,4 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { DiffOptions } from "./DiffOptions";
 
-export type CodeSearchOpts = { repoPath: string, searchText: string, numResults: number, startCommitIndex: number, };
+export type CodeSearchOpts = { repoPath: string, searchText: string, numResults: number, startCommitIndex: number, diffOptions: DiffOptions, };
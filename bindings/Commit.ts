@@ -1,4 +1,5 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CommitSignature } from "./CommitSignature";
 import type { DateResult } from "./DateResult";
 
-export type Commit = { author: string, email: string, date: DateResult, id: string, index: number, parentIds: Array<string>, isMerge: boolean, message: string, stashId: string, refs: Array<string>, filtered: boolean, numSkipped: number, };
+export type Commit = { author: string, email: string, date: DateResult, id: string, index: number, parentIds: Array<string>, isMerge: boolean, message: string, stashId: string, refs: Array<string>, signature: CommitSignature | null, filtered: boolean, numSkipped: number, };
@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ContextSide } from "./ContextSide";
+
+export type ContextRange = { "lines": { side: ContextSide, start: number, end: number, } } | "wholeFile";
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Commit } from "./Commit";
+import type { Patch } from "./Patch";
+import type { WipDiffKind } from "./WipDiffKind";
+import type { WipPatch } from "./WipPatch";
+
+export type ContextSource = { "commit": { commit: Commit, patch: Patch, } } | { "wip": { patch: WipPatch, headCommit: string | null, kind: WipDiffKind, } };
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type CreateCommitOptions = { repoPath: string, message: string, body: string, amend: boolean, keepMessage: boolean, signOff: boolean, author: string | null, date: string | null, coAuthors: Array<string>, sign: boolean | null, noVerify: boolean, };
@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { DiffAlgorithm } from "./DiffAlgorithm";
+import type { WhitespaceMode } from "./WhitespaceMode";
+
+export type DiffOptions = { whitespace: WhitespaceMode, ignoreBlankLines: boolean, contextLines: number | null, algorithm: DiffAlgorithm, detectMoved: boolean, };
@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Hunk } from "./Hunk";
+import type { HunkLine } from "./HunkLine";
+
+export type ExpandedContext = { hunks: Array<Hunk>, hunkLines: Array<HunkLine>, };
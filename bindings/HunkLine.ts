@@ -1,4 +1,7 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CharRange } from "./CharRange";
 import type { HunkLineStatus } from "./HunkLineStatus";
+import type { LineWarning } from "./LineWarning";
+import type { MovedLine } from "./MovedLine";
 
-export type HunkLine = { status: HunkLineStatus, oldNum: number | null, newNum: number | null, hunkIndex: number, text: string, index: number, lineEnding: string, };
+export type HunkLine = { status: HunkLineStatus, oldNum: number | null, newNum: number | null, hunkIndex: number, text: string, index: number, lineEnding: string, changedRanges: Array<CharRange>, moved: MovedLine | null, warnings: Array<LineWarning>, };
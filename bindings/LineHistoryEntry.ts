@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Commit } from "./Commit";
+import type { Hunk } from "./Hunk";
+import type { HunkLine } from "./HunkLine";
+
+export type LineHistoryEntry = { commit: Commit, oldFile: string, newFile: string, hunks: Array<Hunk>, hunkLines: Array<HunkLine>, };
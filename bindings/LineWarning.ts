@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CharRange } from "./CharRange";
+import type { LineWarningKind } from "./LineWarningKind";
+
+export type LineWarning = { kind: LineWarningKind, range: CharRange, };
@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { PatchCheckFailure } from "./PatchCheckFailure";
+import type { PatchCheckFile } from "./PatchCheckFile";
+
+export type PatchCheck = { applies: boolean, files: Array<PatchCheckFile>, failures: Array<PatchCheckFailure>, conflicts: Array<string>, };
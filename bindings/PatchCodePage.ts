@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { DiffStats } from "./DiffStats";
+
+export type PatchCodePage = { stats: DiffStats, html: string | null, offset: number, hasMore: boolean, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Hunk } from "./Hunk";
+import type { HunkLine } from "./HunkLine";
+import type { Patch } from "./Patch";
+
+export type PatchHunks = { patch: Patch, hunks: Array<Hunk>, hunkLines: Array<HunkLine>, };
@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CodeToken } from "./CodeToken";
+import type { DiffStats } from "./DiffStats";
+
+export type PatchTokens = { stats: DiffStats, lines: Array<Array<CodeToken>> | null, };
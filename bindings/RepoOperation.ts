@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { MailboxSession } from "./MailboxSession";
+
+export type RepoOperation = "none" | { "merging": { mergeHeads: Array<string>, } } | { "rebasing": { interactive: boolean, step: number | null, total: number | null, currentCommit: string | null, onto: string | null, headName: string | null, } } | { "cherryPicking": { commit: string, } } | { "reverting": { commit: string, } } | { "applyingMailbox": MailboxSession } | { "bisecting": { start: string, bad: string | null, good: Array<string>, } };
@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { OperationCommand } from "./OperationCommand";
+import type { RepoOperation } from "./RepoOperation";
+
+export type RepoOperationState = { operation: RepoOperation, commands: Array<OperationCommand>, };
@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ApplyTarget } from "./ApplyTarget";
+
+export type ReqApplyPatchOptions = { repoPath: string, patchFile: string, target: ApplyTarget, threeWay: boolean, reverse: boolean, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ColourOutput } from "./ColourOutput";
+import type { ReqBlameOptions } from "./ReqBlameOptions";
+import type { ThemeColour } from "./ThemeColour";
+
+export type ReqBlameHtmlOptions = { blame: ReqBlameOptions, theme: ThemeColour, output: ColourOutput, };
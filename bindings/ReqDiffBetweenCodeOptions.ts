@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ColourOutput } from "./ColourOutput";
+import type { ReqHunksBetweenOptions } from "./ReqHunksBetweenOptions";
+import type { ThemeColour } from "./ThemeColour";
+
+export type ReqDiffBetweenCodeOptions = { between: ReqHunksBetweenOptions, theme: ThemeColour, split: boolean, output: ColourOutput, force: boolean, };
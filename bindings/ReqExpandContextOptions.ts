@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ContextRange } from "./ContextRange";
+import type { ContextSource } from "./ContextSource";
+import type { DiffOptions } from "./DiffOptions";
+
+export type ReqExpandContextOptions = { repoPath: string, source: ContextSource, range: ContextRange, diffOptions: DiffOptions, };
@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { PatchOutput } from "./PatchOutput";
+import type { PatchSelection } from "./PatchSelection";
+
+export type ReqExportPatchesOptions = { repoPath: string, selection: PatchSelection, output: PatchOutput, coverLetter: boolean, numbered: boolean, };
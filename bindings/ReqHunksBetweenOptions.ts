@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { DiffOptions } from "./DiffOptions";
+import type { Patch } from "./Patch";
+
+export type ReqHunksBetweenOptions = { repoPath: string, from: string, to: string, threeDot: boolean, patch: Patch, diffOptions: DiffOptions, };
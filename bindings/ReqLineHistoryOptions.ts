@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { LineRange } from "./LineRange";
+
+export type ReqLineHistoryOptions = { repoPath: string, filePath: string, startCommitId: string | null, range: LineRange, skip: number, num: number, };
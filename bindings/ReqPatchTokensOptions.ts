@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Commit } from "./Commit";
+import type { DiffOptions } from "./DiffOptions";
+import type { Patch } from "./Patch";
+
+export type ReqPatchTokensOptions = { force: boolean, repoPath: string, commit: Commit, patch: Patch, diffOptions: DiffOptions, };
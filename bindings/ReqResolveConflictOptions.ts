@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SectionChoice } from "./SectionChoice";
+import type { WipPatch } from "./WipPatch";
+
+export type ReqResolveConflictOptions = { repoPath: string, patch: WipPatch, choices: Array<SectionChoice>, textHash: string, stage: boolean, };
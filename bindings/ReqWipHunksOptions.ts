@@ -1,4 +1,6 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { DiffOptions } from "./DiffOptions";
+import type { WipDiffKind } from "./WipDiffKind";
 import type { WipPatch } from "./WipPatch";
 
-export type ReqWipHunksOptions = { repoPath: string, patch: WipPatch, headCommit: string | null, };
+export type ReqWipHunksOptions = { repoPath: string, patch: WipPatch, headCommit: string | null, diffOptions: DiffOptions, kind: WipDiffKind, };
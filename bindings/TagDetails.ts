@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CommitSignature } from "./CommitSignature";
+import type { DateResult } from "./DateResult";
+
+export type TagDetails = { id: string, name: string, commitId: string, annotated: boolean, tagger: string, email: string, date: DateResult | null, message: string, signed: boolean, signature: CommitSignature | null, };
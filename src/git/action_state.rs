@@ -12,8 +12,12 @@ use crate::util::global::Global;
 pub struct ActionState {
   pub stdout: Vec<String>,
   pub stderr: Vec<String>,
+  // Output from hooks we run ourselves, kept apart from git's own output.
+  pub hook_output: Vec<String>,
   pub done: bool,
   pub error: Option<ActionError>,
+  // Set by actions that produce a value, e.g. the id of a new commit.
+  pub result: Option<String>,
 }
 
 impl ActionState {
@@ -21,8 +25,10 @@ impl ActionState {
     Self {
       stdout: Vec::new(),
       stderr: Vec::new(),
+      hook_output: Vec::new(),
       done: false,
       error: None,
+      result: None,
     }
   }
 }
@@ -73,6 +79,26 @@ pub fn add_stdout_log(id: u32, text: &str) {
   }
 }
 
+pub fn add_hook_log(id: u32, text: &str) {
+  if let Some(mut action) = ACTIONS.get_by_key(&id) {
+    action.hook_output.push(text.to_string());
+
+    ACTIONS.insert(id, action);
+  } else {
+    eprintln!("add_hook_log: Didn't find action id {}", id);
+  }
+}
+
+pub fn set_action_result(id: u32, result: &str) {
+  if let Some(mut action) = ACTIONS.get_by_key(&id) {
+    action.result = Some(result.to_string());
+
+    ACTIONS.insert(id, action);
+  } else {
+    eprintln!("set_action_result: Didn't find action id {}", id);
+  }
+}
+
 pub fn set_action_error(id: u32, error: ActionError) {
   if let Some(mut action) = ACTIONS.get_by_key(&id) {
     action.error = Some(error);
@@ -0,0 +1,335 @@
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::f;
+use crate::git::run_git::{run_git_err, RunGitOptions};
+use crate::git::run_git_action::run_git_action_with_vec;
+use crate::server::git_request::ReqOptions;
+use crate::server::request_util::{ES, R};
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, TS)]
+#[ts(export)]
+pub enum ApplyTarget {
+  #[default]
+  WorkingTree,
+  // Only the index (--cached).
+  Index,
+  // Both the working tree and the index (--index).
+  Both,
+}
+
+#[derive(Debug, Deserialize, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct ReqApplyPatchOptions {
+  pub repo_path: String,
+  // Path to a unified diff, e.g. from git diff or format-patch.
+  pub patch_file: String,
+  #[serde(default)]
+  pub target: ApplyTarget,
+  #[serde(default)]
+  pub three_way: bool,
+  #[serde(default)]
+  pub reverse: bool,
+}
+
+pub fn apply_patch(options: &ReqApplyPatchOptions) -> u32 {
+  run_git_action_with_vec(&options.repo_path, vec![apply_args(options)])
+}
+
+fn apply_args(options: &ReqApplyPatchOptions) -> Vec<String> {
+  let ReqApplyPatchOptions {
+    patch_file,
+    target,
+    three_way,
+    reverse,
+    ..
+  } = options;
+
+  let mut args = vec![f!("apply")];
+
+  match target {
+    ApplyTarget::WorkingTree => {}
+    ApplyTarget::Index => args.push(f!("--cached")),
+    ApplyTarget::Both => args.push(f!("--index")),
+  }
+  if *three_way {
+    args.push(f!("--3way"));
+  }
+  if *reverse {
+    args.push(f!("--reverse"));
+  }
+
+  args.push(patch_file.clone());
+
+  args
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct PatchCheck {
+  // Applies cleanly. False if there are failures or 3-way conflicts.
+  pub applies: bool,
+  pub files: Vec<PatchCheckFile>,
+  pub failures: Vec<PatchCheckFailure>,
+  // Paths the 3-way merge would leave conflict markers in.
+  pub conflicts: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct PatchCheckFile {
+  pub path: String,
+  // None for binary files.
+  pub added: Option<u32>,
+  pub removed: Option<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct PatchCheckFailure {
+  pub path: String,
+  // Start of the failing hunk in the file. None if the whole file failed.
+  pub line: Option<u32>,
+  pub message: String,
+}
+
+// A dry run of apply_patch. Nothing is changed.
+pub fn check_patch(options: &ReqApplyPatchOptions) -> R<PatchCheck> {
+  let repo_path = &options.repo_path;
+
+  let stat = run_git_err(RunGitOptions {
+    repo_path,
+    args: ["apply", "--numstat", &options.patch_file],
+  })?;
+
+  if stat.stdout.is_empty() && !stat.stderr.is_empty() {
+    return Err(ES::from(stat.stderr.trim()));
+  }
+
+  let mut args = apply_args(options);
+  args.insert(1, f!("--check"));
+  args.insert(2, f!("--verbose"));
+
+  let out = run_git_err(RunGitOptions { repo_path, args })?;
+  let failures = parse_check_failures(&out.stderr);
+  let conflicts = parse_check_conflicts(&out.stderr);
+
+  Ok(PatchCheck {
+    applies: failures.is_empty() && conflicts.is_empty(),
+    files: parse_numstat(&stat.stdout),
+    failures,
+    conflicts,
+  })
+}
+
+// "3\t1\tsrc/a.rs", with "-" for binary files.
+fn parse_numstat(out: &str) -> Vec<PatchCheckFile> {
+  out
+    .lines()
+    .filter_map(|line| {
+      let mut parts = line.splitn(3, '\t');
+      let added = parts.next()?;
+      let removed = parts.next()?;
+
+      Some(PatchCheckFile {
+        path: parts.next()?.to_string(),
+        added: added.parse().ok(),
+        removed: removed.parse().ok(),
+      })
+    })
+    .collect()
+}
+
+/*
+"error: patch failed: <path>:<line>" is a hunk that didn't apply. It's usually followed
+by "error: <path>: patch does not apply", which we skip as the hunk covers it. Other
+"error: <path>: <message>" lines are for the whole file. Older gits try the 3-way merge
+after the direct apply fails, so errors for files it then applies are dropped.
+ */
+fn parse_check_failures(stderr: &str) -> Vec<PatchCheckFailure> {
+  let mut failures: Vec<PatchCheckFailure> = Vec::new();
+
+  for line in stderr.lines() {
+    let Some(error) = line.strip_prefix("error: ") else {
+      continue;
+    };
+
+    if let Some(location) = error.strip_prefix("patch failed: ") {
+      if let Some((path, num)) = location.rsplit_once(':') {
+        failures.push(PatchCheckFailure {
+          path: path.to_string(),
+          line: num.parse().ok(),
+          message: f!("Hunk at line {} doesn't match", num),
+        });
+      }
+    } else if let Some((path, message)) = error.split_once(": ") {
+      let has_hunk_failure = failures.iter().any(|f| f.path == path && f.line.is_some());
+
+      if !(message == "patch does not apply" && has_hunk_failure) {
+        failures.push(PatchCheckFailure {
+          path: path.to_string(),
+          line: None,
+          message: message.to_string(),
+        });
+      }
+    }
+  }
+
+  let merged: Vec<&str> = stderr
+    .lines()
+    .filter_map(|line| parse_three_way_result(line).map(|(path, _)| path))
+    .collect();
+  failures.retain(|f| !merged.contains(&f.path.as_str()));
+
+  failures
+}
+
+fn parse_check_conflicts(stderr: &str) -> Vec<String> {
+  stderr
+    .lines()
+    .filter_map(parse_three_way_result)
+    .filter(|(_, conflicted)| *conflicted)
+    .map(|(path, _)| path.to_string())
+    .collect()
+}
+
+// "Applied patch to '<path>' cleanly." or "... with conflicts." Returns if conflicted.
+fn parse_three_way_result(line: &str) -> Option<(&str, bool)> {
+  let rest = line.strip_prefix("Applied patch to '")?;
+
+  if let Some(path) = rest.strip_suffix("' with conflicts.") {
+    Some((path, true))
+  } else {
+    rest.strip_suffix("' cleanly.").map(|path| (path, false))
+  }
+}
+
+#[derive(Debug, Deserialize, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct ReqApplyMailboxOptions {
+  pub repo_path: String,
+  // An mbox or patch files from format-patch, applied in order.
+  pub files: Vec<String>,
+  // Fall back to a 3-way merge when patches don't apply cleanly.
+  pub three_way: bool,
+}
+
+// Progress is reported by load_repo_operation_state while the session is stopped.
+pub fn apply_mailbox(options: &ReqApplyMailboxOptions) -> u32 {
+  let ReqApplyMailboxOptions {
+    repo_path,
+    files,
+    three_way,
+  } = options;
+
+  let mut args = vec![f!("am")];
+
+  if *three_way {
+    args.push(f!("--3way"));
+  }
+  args.extend(files.iter().cloned());
+
+  run_git_action_with_vec(repo_path, vec![args])
+}
+
+pub fn continue_mailbox(options: &ReqOptions) -> u32 {
+  run_git_action_with_vec(&options.repo_path, vec![vec![f!("am"), f!("--continue")]])
+}
+
+pub fn skip_mailbox_patch(options: &ReqOptions) -> u32 {
+  run_git_action_with_vec(&options.repo_path, vec![vec![f!("am"), f!("--skip")]])
+}
+
+pub fn abort_mailbox(options: &ReqOptions) -> u32 {
+  run_git_action_with_vec(&options.repo_path, vec![vec![f!("am"), f!("--abort")]])
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::git::actions::apply_patch::{
+    check_patch, parse_check_conflicts, parse_check_failures, parse_numstat,
+    ReqApplyPatchOptions,
+  };
+  use crate::git::test_repo::TestRepo;
+
+  #[test]
+  fn test_parse_numstat() {
+    let files = parse_numstat("3\t1\tsrc/a b.rs\n-\t-\timage.png\n");
+
+    assert_eq!(files.len(), 2);
+    assert_eq!(files[0].path, "src/a b.rs");
+    assert_eq!(files[0].added, Some(3));
+    assert_eq!(files[1].removed, None);
+  }
+
+  #[test]
+  fn test_parse_check_failures() {
+    let stderr = "Checking patch g...
+error: while searching for:
+1
+2
+
+error: patch failed: g:12
+error: g: patch does not apply
+Checking patch f4...
+error: f4: already exists in working directory
+";
+
+    let failures = parse_check_failures(stderr);
+
+    assert_eq!(failures.len(), 2);
+    assert_eq!(failures[0].path, "g");
+    assert_eq!(failures[0].line, Some(12));
+    assert_eq!(failures[1].path, "f4");
+    assert_eq!(failures[1].line, None);
+    assert_eq!(failures[1].message, "already exists in working directory");
+  }
+
+  #[test]
+  fn test_parse_three_way_check() {
+    let stderr = "Checking patch f...
+error: patch failed: f:1
+Falling back to three-way merge...
+Applied patch to 'f' with conflicts.
+Checking patch g...
+Applied patch to 'g' cleanly.
+Checking patch h...
+error: h: does not exist in index
+";
+
+    let failures = parse_check_failures(stderr);
+
+    assert_eq!(failures.len(), 1);
+    assert_eq!(failures[0].path, "h");
+    assert_eq!(parse_check_conflicts(stderr), vec!["f"]);
+  }
+
+  #[test]
+  fn test_check_patch_three_way_conflict() {
+    let repo = TestRepo::new("check_patch_three_way_conflict");
+    repo.write("f", "1\n2\n3\n");
+    repo.commit("base");
+    repo.write("f", "1\nA\n3\n");
+    repo.write("p.patch", repo.git(&["diff"]) + "\n");
+    repo.write("f", "1\nB\n3\n");
+    repo.git(&["commit", "-q", "-am", "b"]);
+
+    let check = check_patch(&ReqApplyPatchOptions {
+      repo_path: repo.path_str(),
+      patch_file: repo.path.join("p.patch").to_string_lossy().to_string(),
+      target: Default::default(),
+      three_way: true,
+      reverse: false,
+    })
+    .unwrap();
+
+    assert!(!check.applies);
+    assert!(check.failures.is_empty());
+    assert_eq!(check.conflicts, vec!["f"]);
+  }
+}
@@ -0,0 +1,381 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::thread;
+
+use serde::Deserialize;
+use ts_rs::TS;
+
+use crate::git::action_state::{
+  add_hook_log, set_action_done, set_action_error, set_action_result, start_action,
+};
+use crate::git::git_settings::GIT_PATH;
+use crate::git::git_version::GitVersion;
+use crate::git::queries::config::read_config_value;
+use crate::git::run_git::{run_git_err, RunGitOptions};
+use crate::git::run_git_action::ActionError::{Hook, IO};
+use crate::git::run_git_action::{args_with_config, run_git_action_inner, ActionError};
+use crate::git::store::STORE;
+use crate::server::git_request::ReqOptions;
+use crate::server::request_util::R;
+use crate::{dprintln, f};
+
+const MESSAGE_FILE: &str = "GITFIEND_COMMIT_EDITMSG";
+
+#[derive(Debug, Clone, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct CreateCommitOptions {
+  pub repo_path: String,
+  pub message: String,
+  pub body: String,
+  pub amend: bool,
+  // Only used with amend. Message and body are ignored.
+  pub keep_message: bool,
+  pub sign_off: bool,
+  // E.g. "Name <email@example.com>"
+  pub author: Option<String>,
+  pub date: Option<String>,
+  pub co_authors: Vec<String>,
+  // None leaves it up to commit.gpgSign.
+  pub sign: Option<bool>,
+  pub no_verify: bool,
+}
+
+/*
+Returns an action id. Once done, the action result is the new commit id.
+
+With git 2.36+ we run pre-commit and commit-msg ourselves via "git hook run" so their
+output ends up in hook_output rather than mixed in with git's stderr. Git still runs
+prepare-commit-msg and post-commit itself.
+ */
+pub fn create_commit(options: &CreateCommitOptions) -> u32 {
+  let id = start_action();
+  let git_version = STORE.get_git_version();
+  let options = options.clone();
+
+  thread::spawn(move || match run_commit(id, &options, git_version) {
+    Ok(commit_id) => {
+      set_action_result(id, &commit_id);
+      set_action_done(id);
+    }
+    Err(e) => {
+      set_action_error(id, e);
+    }
+  });
+
+  id
+}
+
+fn run_commit(
+  id: u32,
+  options: &CreateCommitOptions,
+  git_version: GitVersion,
+) -> Result<String, ActionError> {
+  let CreateCommitOptions {
+    repo_path,
+    amend,
+    keep_message,
+    co_authors,
+    ..
+  } = options;
+
+  let message = if *amend && *keep_message {
+    load_head_message(repo_path)?
+  } else {
+    build_message(&options.message, &options.body)
+  };
+  let message = add_co_author_trailers(&message, co_authors);
+
+  let message_file = STORE.get_repo_path(repo_path)?.git_path.join(MESSAGE_FILE);
+  fs::write(&message_file, message)?;
+
+  let result = commit_with_message_file(id, options, &message_file, git_version);
+
+  if let Err(_e) = fs::remove_file(&message_file) {
+    dprintln!("{}", _e);
+  }
+
+  result
+}
+
+fn commit_with_message_file(
+  id: u32,
+  options: &CreateCommitOptions,
+  message_file: &Path,
+  git_version: GitVersion,
+) -> Result<String, ActionError> {
+  let CreateCommitOptions {
+    repo_path,
+    sign,
+    no_verify,
+    ..
+  } = options;
+
+  let run_hooks = !*no_verify && hook_run_supported(&git_version);
+
+  if run_hooks {
+    run_hook(id, repo_path, "pre-commit", &[])?;
+    run_hook(id, repo_path, "commit-msg", &[message_file])?;
+  }
+
+  let signing = signing_args(
+    *sign,
+    read_config_value(repo_path, "gpg.format"),
+    read_config_value(repo_path, "user.signingKey"),
+    read_config_value(repo_path, "gpg.ssh.defaultKeyCommand").is_some(),
+  )?;
+
+  let args = commit_args(options, message_file, run_hooks || *no_verify, signing);
+
+  run_git_action_inner(id, repo_path.clone(), git_version, args)?;
+
+  Ok(
+    run_git_err(RunGitOptions {
+      repo_path,
+      args: ["rev-parse", "HEAD"],
+    })?
+    .stdout
+    .trim()
+    .to_string(),
+  )
+}
+
+fn commit_args(
+  options: &CreateCommitOptions,
+  message_file: &Path,
+  skip_hooks: bool,
+  signing: Vec<String>,
+) -> Vec<String> {
+  let CreateCommitOptions {
+    amend,
+    sign_off,
+    author,
+    date,
+    ..
+  } = options;
+
+  let mut args = vec![
+    f!("commit"),
+    f!("--file={}", message_file.to_string_lossy()),
+  ];
+
+  if *amend {
+    args.push(f!("--amend"));
+  }
+  if *sign_off {
+    args.push(f!("--signoff"));
+  }
+  if let Some(author) = author {
+    args.push(f!("--author={}", author));
+  }
+  if let Some(date) = date {
+    args.push(f!("--date={}", date));
+  }
+  if skip_hooks {
+    args.push(f!("--no-verify"));
+  }
+
+  args.extend(signing);
+
+  args
+}
+
+fn build_message(message: &str, body: &str) -> String {
+  let message = message.trim();
+  let body = body.trim();
+
+  if body.is_empty() {
+    f!("{}\n", message)
+  } else {
+    f!("{}\n\n{}\n", message, body)
+  }
+}
+
+fn add_co_author_trailers(message: &str, co_authors: &[String]) -> String {
+  if co_authors.is_empty() {
+    return message.to_string();
+  }
+
+  let trailers = co_authors
+    .iter()
+    .map(|a| f!("Co-authored-by: {}", a.trim()))
+    .filter(|t| !message.contains(t.as_str()))
+    .collect::<Vec<String>>();
+
+  if trailers.is_empty() {
+    return message.to_string();
+  }
+
+  let message = message.trim_end();
+  let last_paragraph = message.rsplit("\n\n").next().unwrap_or("");
+
+  // Join an existing trailer block rather than starting a new paragraph.
+  let separator = if message.contains("\n\n") && is_trailer_block(last_paragraph) {
+    "\n"
+  } else {
+    "\n\n"
+  };
+
+  f!("{}{}{}\n", message, separator, trailers.join("\n"))
+}
+
+fn is_trailer_block(paragraph: &str) -> bool {
+  paragraph.lines().all(|line| {
+    if let Some((key, _)) = line.split_once(": ") {
+      !key.is_empty() && key.chars().all(|c| c.is_alphanumeric() || c == '-')
+    } else {
+      false
+    }
+  })
+}
+
+fn signing_args(
+  sign: Option<bool>,
+  format: Option<String>,
+  key: Option<String>,
+  has_default_key_command: bool,
+) -> Result<Vec<String>, ActionError> {
+  match sign {
+    None => Ok(Vec::new()),
+    Some(false) => Ok(vec![f!("--no-gpg-sign")]),
+    Some(true) => {
+      if let Some(key) = key {
+        return Ok(vec![f!("--gpg-sign={}", key)]);
+      }
+
+      if format.unwrap_or_default() == "ssh" && !has_default_key_command {
+        return Err(IO(f!(
+          "SSH signing requires user.signingKey or gpg.ssh.defaultKeyCommand to be set"
+        )));
+      }
+
+      Ok(vec![f!("--gpg-sign")])
+    }
+  }
+}
+
+fn load_head_message(repo_path: &str) -> R<String> {
+  Ok(
+    run_git_err(RunGitOptions {
+      repo_path,
+      args: ["log", "-1", "--format=%B"],
+    })?
+    .stdout,
+  )
+}
+
+// "git hook run" was added in 2.36.
+fn hook_run_supported(version: &GitVersion) -> bool {
+  version.major > 2 || (version.major == 2 && version.minor >= 36)
+}
+
+fn run_hook(
+  id: u32,
+  repo_path: &str,
+  hook: &str,
+  hook_args: &[&Path],
+) -> Result<(), ActionError> {
+  let mut args = vec![
+    f!("hook"),
+    f!("run"),
+    f!("--ignore-missing"),
+    hook.to_string(),
+  ];
+
+  if !hook_args.is_empty() {
+    args.push(f!("--"));
+    args.extend(hook_args.iter().map(|a| a.to_string_lossy().to_string()));
+  }
+
+  let out = Command::new(GIT_PATH.as_path())
+    .args(args_with_config(args, STORE.get_git_version()))
+    .current_dir(repo_path)
+    .output()?;
+
+  for line in String::from_utf8_lossy(&out.stdout)
+    .lines()
+    .chain(String::from_utf8_lossy(&out.stderr).lines())
+  {
+    add_hook_log(id, line);
+  }
+
+  if !out.status.success() {
+    return Err(Hook(hook.to_string()));
+  }
+
+  Ok(())
+}
+
+// Returns the contents of commit.template, for pre-filling the commit message.
+pub fn load_commit_template(options: &ReqOptions) -> R<Option<String>> {
+  let ReqOptions { repo_path } = options;
+
+  if let Some(path) = read_config_value(repo_path, "commit.template") {
+    let path = expand_home(&path, repo_path);
+
+    return Ok(Some(fs::read_to_string(path)?));
+  }
+
+  Ok(None)
+}
+
+fn expand_home(path: &str, repo_path: &str) -> PathBuf {
+  if let Some(rest) = path.strip_prefix("~/") {
+    if let Some(dirs) = directories::BaseDirs::new() {
+      return dirs.home_dir().join(rest);
+    }
+  }
+
+  Path::new(repo_path).join(path)
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::git::actions::commit::{
+    add_co_author_trailers, build_message, signing_args,
+  };
+
+  #[test]
+  fn test_build_message() {
+    assert_eq!(build_message("Subject", ""), "Subject\n");
+    assert_eq!(build_message("Subject ", "\nBody\n"), "Subject\n\nBody\n");
+  }
+
+  #[test]
+  fn test_add_co_author_trailers() {
+    let authors = vec!["A <a@a.com>".to_string()];
+
+    assert_eq!(
+      add_co_author_trailers("Subject\n", &authors),
+      "Subject\n\nCo-authored-by: A <a@a.com>\n"
+    );
+
+    assert_eq!(
+      add_co_author_trailers("Subject\n\nSigned-off-by: B <b@b.com>\n", &authors),
+      "Subject\n\nSigned-off-by: B <b@b.com>\nCo-authored-by: A <a@a.com>\n"
+    );
+
+    // Don't add the same trailer twice when amending.
+    let message = "Subject\n\nCo-authored-by: A <a@a.com>\n";
+    assert_eq!(add_co_author_trailers(message, &authors), message);
+  }
+
+  #[test]
+  fn test_signing_args() {
+    assert!(signing_args(None, None, None, false).unwrap().is_empty());
+    assert_eq!(
+      signing_args(Some(false), None, None, false).unwrap(),
+      vec!["--no-gpg-sign"]
+    );
+    assert_eq!(
+      signing_args(Some(true), None, Some("ABC".to_string()), false).unwrap(),
+      vec!["--gpg-sign=ABC"]
+    );
+    assert!(signing_args(Some(true), Some("ssh".to_string()), None, false).is_err());
+    assert_eq!(
+      signing_args(Some(true), Some("ssh".to_string()), None, true).unwrap(),
+      vec!["--gpg-sign"]
+    );
+  }
+}
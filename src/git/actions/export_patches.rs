@@ -0,0 +1,265 @@
+use ahash::AHashMap;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::f;
+use crate::git::git_types::Commit;
+use crate::git::run_git::{run_git_err, RunGitOptions};
+use crate::git::store::STORE;
+use crate::server::request_util::{ES, R};
+
+#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub enum PatchSelection {
+  // One or more commits, in any order. They don't need to be next to each other.
+  Commits { ids: Vec<String> },
+  // Commits in "to" that aren't in "from", like from..to.
+  Range { from: String, to: String },
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub enum PatchOutput {
+  // A file per commit, named like git format-patch does.
+  Files { dir: String },
+  // All patches in one mbox file.
+  Mbox { file: String },
+  // All patches returned as text, e.g. for the clipboard.
+  Stdout,
+}
+
+#[derive(Debug, Deserialize, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct ReqExportPatchesOptions {
+  pub repo_path: String,
+  pub selection: PatchSelection,
+  pub output: PatchOutput,
+  pub cover_letter: bool,
+  // "[PATCH n/m]" instead of "[PATCH]".
+  pub numbered: bool,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct ExportedPatches {
+  // Paths of the files written, including the cover letter.
+  pub files: Vec<String>,
+  // Only set for PatchOutput::Stdout.
+  pub text: Option<String>,
+}
+
+pub fn export_patches(options: &ReqExportPatchesOptions) -> R<ExportedPatches> {
+  let ReqExportPatchesOptions {
+    repo_path,
+    selection,
+    output,
+    cover_letter,
+    numbered,
+  } = options;
+
+  let mut args = vec![f!("format-patch")];
+
+  if *numbered {
+    args.push(f!("--numbered"));
+  } else {
+    args.push(f!("--no-numbered"));
+  }
+  if *cover_letter {
+    args.push(f!("--cover-letter"));
+  }
+
+  match output {
+    PatchOutput::Files { dir } => {
+      args.push(f!("--output-directory"));
+      args.push(dir.clone());
+    }
+    PatchOutput::Mbox { .. } | PatchOutput::Stdout => args.push(f!("--stdout")),
+  }
+
+  args.extend(get_revision_args(repo_path, selection)?);
+
+  let out = run_git_err(RunGitOptions { repo_path, args })?;
+
+  if out.stdout.is_empty() && !out.stderr.is_empty() {
+    return Err(ES::from(out.stderr.trim()));
+  }
+
+  match output {
+    PatchOutput::Files { .. } => Ok(ExportedPatches {
+      files: out.stdout.lines().map(|l| l.to_string()).collect(),
+      text: None,
+    }),
+    PatchOutput::Mbox { file } => {
+      std::fs::write(file, &out.stdout)?;
+
+      Ok(ExportedPatches {
+        files: vec![file.clone()],
+        text: None,
+      })
+    }
+    PatchOutput::Stdout => Ok(ExportedPatches {
+      files: Vec::new(),
+      text: Some(out.stdout),
+    }),
+  }
+}
+
+fn get_revision_args(repo_path: &str, selection: &PatchSelection) -> R<Vec<String>> {
+  match selection {
+    PatchSelection::Commits { ids } => {
+      if ids.is_empty() {
+        return Err(ES::from("No commits selected"));
+      }
+
+      let ordered = STORE
+        .get_commits_and_refs(&repo_path.to_string())
+        .and_then(|(commits, _)| order_newest_first(ids, &commits));
+
+      Ok(get_commit_args(ids, ordered))
+    }
+    PatchSelection::Range { from, to } => Ok(vec![f!("{}..{}", from, to)]),
+  }
+}
+
+/*
+format-patch writes commits in the reverse of the order it walks them. With
+--no-walk=unsorted that's the order given, so we give them newest first (as in our loaded
+commits) and the series starts with the oldest. Commits we haven't loaded are sorted by
+git instead. On its own, one commit would mean "<id>..HEAD", so that needs "-1".
+ */
+fn get_commit_args(ids: &[String], ordered: Option<Vec<String>>) -> Vec<String> {
+  let (walk, ids) = match ordered {
+    Some(ids) => (f!("--no-walk=unsorted"), ids),
+    None => (f!("--no-walk=sorted"), ids.to_vec()),
+  };
+
+  if ids.len() == 1 {
+    return [vec![f!("-1")], ids].concat();
+  }
+
+  [vec![walk], ids].concat()
+}
+
+// None if any of the ids aren't in commits.
+fn order_newest_first(ids: &[String], commits: &[Commit]) -> Option<Vec<String>> {
+  let indices: AHashMap<&str, usize> =
+    commits.iter().map(|c| (c.id.as_str(), c.index)).collect();
+
+  let mut ordered = ids
+    .iter()
+    .map(|id| Some((*indices.get(id.as_str())?, id.clone())))
+    .collect::<Option<Vec<(usize, String)>>>()?;
+
+  ordered.sort_by_key(|(index, _)| *index);
+  ordered.dedup();
+
+  Some(ordered.into_iter().map(|(_, id)| id).collect())
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::git::actions::export_patches::{get_commit_args, order_newest_first};
+  use crate::git::git_types::{Commit, DateResult};
+  use crate::git::test_repo::TestRepo;
+
+  fn commit(id: &str, index: usize) -> Commit {
+    Commit {
+      author: String::new(),
+      email: String::new(),
+      date: DateResult {
+        ms: 0,
+        adjustment: 0,
+      },
+      id: id.to_string(),
+      index,
+      parent_ids: Vec::new(),
+      is_merge: false,
+      message: String::new(),
+      stash_id: String::new(),
+      refs: Vec::new(),
+      signature: None,
+      filtered: false,
+      num_skipped: 0,
+    }
+  }
+
+  #[test]
+  fn test_order_newest_first() {
+    let commits = vec![commit("c", 0), commit("b", 1), commit("a", 2)];
+    let ids = |ids: &[&str]| ids.iter().map(|id| id.to_string()).collect::<Vec<_>>();
+
+    assert_eq!(
+      order_newest_first(&ids(&["a", "c", "b"]), &commits),
+      Some(ids(&["c", "b", "a"]))
+    );
+    assert_eq!(
+      order_newest_first(&ids(&["a", "a"]), &commits),
+      Some(ids(&["a"]))
+    );
+    assert_eq!(order_newest_first(&ids(&["a", "x"]), &commits), None);
+  }
+
+  #[test]
+  fn test_get_commit_args() {
+    let ids = |ids: &[&str]| ids.iter().map(|id| id.to_string()).collect::<Vec<_>>();
+
+    assert_eq!(
+      get_commit_args(&ids(&["a", "c"]), Some(ids(&["c", "a"]))),
+      ids(&["--no-walk=unsorted", "c", "a"])
+    );
+    assert_eq!(
+      get_commit_args(&ids(&["a", "c"]), None),
+      ids(&["--no-walk=sorted", "a", "c"])
+    );
+    assert_eq!(
+      get_commit_args(&ids(&["a"]), Some(ids(&["a"]))),
+      ids(&["-1", "a"])
+    );
+    assert_eq!(get_commit_args(&ids(&["a"]), None), ids(&["-1", "a"]));
+    // Duplicates of one commit are still just that commit.
+    assert_eq!(
+      get_commit_args(&ids(&["a", "a"]), Some(ids(&["a"]))),
+      ids(&["-1", "a"])
+    );
+  }
+
+  #[test]
+  fn test_commit_args_with_git() {
+    let repo = TestRepo::new("export_patches_commit_args");
+    let ids: Vec<String> = ["c1", "c2", "c3"]
+      .iter()
+      .map(|name| {
+        repo.write(name, name);
+        repo.commit(name)
+      })
+      .collect();
+
+    let subjects = |args: Vec<String>| {
+      let mut all = vec!["format-patch", "--stdout"];
+      all.extend(args.iter().map(|a| a.as_str()));
+
+      repo
+        .git(&all)
+        .lines()
+        .filter_map(|l| l.strip_prefix("Subject: "))
+        .map(|l| l.to_string())
+        .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+      subjects(get_commit_args(&ids[..1], Some(ids[..1].to_vec()))),
+      ["[PATCH] c1"]
+    );
+    assert_eq!(
+      subjects(get_commit_args(
+        &[ids[0].clone(), ids[2].clone()],
+        Some(vec![ids[2].clone(), ids[0].clone()])
+      )),
+      ["[PATCH 1/2] c1", "[PATCH 2/2] c3"]
+    );
+  }
+}
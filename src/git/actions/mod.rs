@@ -1,8 +1,12 @@
 pub(crate) mod add;
+pub(crate) mod apply_patch;
 pub(crate) mod clone;
 pub(crate) mod command;
+pub(crate) mod commit;
 pub(crate) mod create_repo;
 pub(crate) mod credentials;
+pub(crate) mod export_patches;
 pub(crate) mod fake_action;
 pub(crate) mod fetch;
 pub(crate) mod stash;
+pub(crate) mod tags;
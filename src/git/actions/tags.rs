@@ -0,0 +1,109 @@
+use serde::Deserialize;
+use ts_rs::TS;
+
+use crate::f;
+use crate::git::run_git_action::run_git_action_with_vec;
+
+#[derive(Debug, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct CreateTagOptions {
+  pub repo_path: String,
+  pub name: String,
+  pub commit_id: String,
+  // A lightweight tag is created if there's no message and we aren't signing.
+  pub message: Option<String>,
+  pub sign: bool,
+}
+
+pub fn create_tag(options: &CreateTagOptions) -> u32 {
+  let CreateTagOptions {
+    repo_path,
+    name,
+    commit_id,
+    message,
+    sign,
+  } = options;
+
+  let mut args = vec![f!("tag")];
+
+  if *sign {
+    args.push(f!("--sign"));
+  } else if message.is_some() {
+    args.push(f!("--annotate"));
+  }
+
+  if *sign || message.is_some() {
+    args.push(f!("--message={}", message.clone().unwrap_or_default()));
+  }
+
+  args.push(name.clone());
+  args.push(commit_id.clone());
+
+  run_git_action_with_vec(repo_path, vec![args])
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct DeleteTagOptions {
+  pub repo_path: String,
+  pub name: String,
+  pub local: bool,
+  // Also delete from this remote.
+  pub remote: Option<String>,
+}
+
+pub fn delete_tag(options: &DeleteTagOptions) -> u32 {
+  let DeleteTagOptions {
+    repo_path,
+    name,
+    local,
+    remote,
+  } = options;
+
+  let mut commands = Vec::new();
+
+  if let Some(remote) = remote {
+    commands.push(vec![
+      f!("push"),
+      remote.clone(),
+      f!("--delete"),
+      f!("refs/tags/{}", name),
+    ]);
+  }
+
+  if *local {
+    commands.push(vec![f!("tag"), f!("--delete"), name.clone()]);
+  }
+
+  run_git_action_with_vec(repo_path, commands)
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct PushTagsOptions {
+  pub repo_path: String,
+  pub remote: String,
+  // Push all tags if empty.
+  pub tag_names: Vec<String>,
+}
+
+pub fn push_tags(options: &PushTagsOptions) -> u32 {
+  let PushTagsOptions {
+    repo_path,
+    remote,
+    tag_names,
+  } = options;
+
+  let mut args = vec![f!("push"), remote.clone()];
+
+  if tag_names.is_empty() {
+    args.push(f!("--tags"));
+  } else {
+    args.extend(tag_names.iter().map(|n| f!("refs/tags/{}", n)));
+  }
+
+  run_git_action_with_vec(repo_path, vec![args])
+}
@@ -1,5 +1,5 @@
-use std::cmp::Ordering;
-use std::fs;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
 use std::path::Path;
 
 use serde::Deserialize;
@@ -9,6 +9,7 @@ use crate::git::conflicts::conflicted_file::{
   BlankLine, CFLine, CFSection, CFSectionLine, CFSide, ConflictLine, ConflictedFile,
   OkLine, SlotLine,
 };
+use crate::git::conflicts::file_text::read_file_text;
 use crate::git::git_types::WipPatch;
 use crate::git::queries::refs::P_REF_NAME;
 use crate::parser::parse_all;
@@ -26,16 +27,41 @@ pub fn load_conflicted_file(options: &LoadConflictOptions) -> R<ConflictedFile>
   let LoadConflictOptions { repo_path, patch } = options;
 
   let path = Path::new(repo_path).join(&patch.new_file);
-  let text = fs::read_to_string(path)?;
+  let file_text = read_file_text(&path)?;
+  let mut file = parse_conflicted_file(&file_text.text);
 
+  file.encoding = file_text.encoding.name().to_string();
+  file.bom = file_text.bom;
+
+  Ok(file)
+}
+
+pub fn parse_conflicted_file(text: &str) -> ConflictedFile {
   let lines = text.lines().collect::<Vec<&str>>();
 
-  Ok(conflicted_lines(lines))
+  let mut file = conflicted_lines(lines);
+
+  if text.contains("\r\n") {
+    file.line_ending = String::from("\r\n");
+  }
+  file.trailing_newline = text.is_empty() || text.ends_with('\n');
+  file.text_hash = hash_text(text);
+
+  file
 }
 
-const CONFLICT_START: &str = "<<<<<<<";
-const CONFLICT_MIDDLE: &str = "=======";
-const CONFLICT_END: &str = ">>>>>>>";
+pub fn hash_text(text: &str) -> String {
+  let mut hasher = DefaultHasher::new();
+  text.hash(&mut hasher);
+
+  format!("{:x}", hasher.finish())
+}
+
+pub const CONFLICT_START: &str = "<<<<<<<";
+// Starts the base section in diff3 and zdiff3 styles. Comes before CONFLICT_MIDDLE.
+pub const CONFLICT_BASE: &str = "|||||||";
+pub const CONFLICT_MIDDLE: &str = "=======";
+pub const CONFLICT_END: &str = ">>>>>>>";
 
 fn conflicted_lines(lines: Vec<&str>) -> ConflictedFile {
   let mut file = ConflictedFile::new();
@@ -49,9 +75,21 @@ fn conflicted_lines(lines: Vec<&str>) -> ConflictedFile {
       in_section = true;
       side = CFSide::Left;
 
+      if file.sections.get(section).is_none() {
+        file.sections.push(CFSection::new());
+      }
+
       if file.ref_name_top.is_empty() {
         file.ref_name_top = parse_ref_name(str_line);
       }
+    } else if in_section && str_line.starts_with(CONFLICT_BASE) {
+      side = CFSide::Base;
+      // Base can be empty with zdiff3, but it's still there.
+      file.sections[section].get_mut(&side);
+
+      if file.ref_name_base.is_empty() {
+        file.ref_name_base = str_line[CONFLICT_BASE.len()..].trim().to_string();
+      }
     } else if str_line.starts_with(CONFLICT_MIDDLE) {
       side = CFSide::Right;
     } else if str_line.starts_with(CONFLICT_END) {
@@ -69,13 +107,6 @@ fn conflicted_lines(lines: Vec<&str>) -> ConflictedFile {
       }
 
       if in_section {
-        if file.sections.get(section).is_none() {
-          file.sections.push(CFSection {
-            left: vec![],
-            right: vec![],
-          });
-        }
-
         let index_in_section = file.sections[section].get(&side).len();
 
         file.sections[section]
@@ -113,28 +144,23 @@ fn parse_ref_name(line: &str) -> String {
   String::new()
 }
 
+// Pads each side to the same number of rows, including base if there is one.
 fn balance_section_with_blanks(file: &mut ConflictedFile, section: usize) {
-  let CFSection { left, right } = &mut file.sections[section];
-
-  match right.len().cmp(&left.len()) {
-    Ordering::Greater => {
-      let left_blanks = right.len() - left.len();
-
-      for _ in 0..left_blanks {
-        left.push(CFSectionLine::Blank(BlankLine { section }));
-      }
-    }
-    Ordering::Less => {
-      let right_blanks = left.len() - right.len();
-
-      for _ in 0..right_blanks {
-        right.push(CFSectionLine::Blank(BlankLine { section }));
-      }
-    }
-    Ordering::Equal => {}
-  };
+  let CFSection { left, right, base } = &mut file.sections[section];
+
+  let rows = left
+    .len()
+    .max(right.len())
+    .max(base.as_ref().map(|b| b.len()).unwrap_or(0));
+
+  for side in [Some(left), Some(right), base.as_mut()]
+    .into_iter()
+    .flatten()
+  {
+    side.resize(rows, CFSectionLine::Blank(BlankLine { section }));
+  }
 
-  for i in 0..right.len() {
+  for i in 0..rows {
     file
       .lines
       .push(CFLine::Slot(SlotLine { section, index: i }));
@@ -145,8 +171,8 @@ fn balance_section_with_blanks(file: &mut ConflictedFile, section: usize) {
 mod tests {
   use crate::git::conflicts::api::conflicted_lines;
   use crate::git::conflicts::conflicted_file::{
-    CFLine, CFSection, CFSectionLine, CFSide, ConflictLine, ConflictedFile, OkLine,
-    SlotLine,
+    BlankLine, CFLine, CFSection, CFSectionLine, CFSide, ConflictLine, ConflictedFile,
+    OkLine, SlotLine,
   };
 
   #[test]
@@ -179,11 +205,17 @@ mod tests {
           section: 0,
           key: String::from("Right-0-0"),
         })],
+        base: None,
       }],
       ref_name_top: String::from("HEAD"),
       ref_name_bottom: String::from("Local/B"),
+      ref_name_base: String::new(),
       line_ending: String::from("\n"),
+      encoding: String::from("UTF-8"),
+      bom: false,
       max_line_length: 3,
+      trailing_newline: true,
+      text_hash: String::new(),
     };
 
     assert_eq!(res, expected);
@@ -229,13 +261,75 @@ mod tests {
           section: 0,
           key: String::from("Right-0-0"),
         })],
+        base: None,
       }],
       ref_name_top: String::from("HEAD"),
       ref_name_bottom: String::from("Local/B"),
+      ref_name_base: String::new(),
       line_ending: String::from("\n"),
+      encoding: String::from("UTF-8"),
+      bom: false,
       max_line_length: 6,
+      trailing_newline: true,
+      text_hash: String::new(),
     };
 
     assert_eq!(res, expected);
   }
+
+  #[test]
+  fn test_conflicted_file_diff3() {
+    let str_lines = vec![
+      "<<<<<<< HEAD",
+      "abc",
+      "||||||| merged common ancestors",
+      "a",
+      "b",
+      "c",
+      "=======",
+      "cba",
+      ">>>>>>> refs/heads/B",
+    ];
+
+    let res = conflicted_lines(str_lines);
+    let section = &res.sections[0];
+
+    assert_eq!(res.ref_name_base, "merged common ancestors");
+    assert_eq!(res.lines.len(), 3);
+    assert_eq!(section.left.len(), 3);
+    assert_eq!(section.right.len(), 3);
+    assert_eq!(
+      section.base.as_ref().unwrap()[1],
+      CFSectionLine::Conflict(ConflictLine {
+        text: String::from("b"),
+        side: CFSide::Base,
+        section: 0,
+        key: String::from("Base-0-1"),
+      })
+    );
+    assert!(section.left.iter().all(|l| match l {
+      CFSectionLine::Conflict(c) => c.side == CFSide::Left,
+      CFSectionLine::Blank(_) => true,
+    }));
+  }
+
+  #[test]
+  fn test_conflicted_file_zdiff3_empty_base() {
+    let str_lines = vec![
+      "<<<<<<< HEAD",
+      "abc",
+      "||||||| 1234567",
+      "=======",
+      "cba",
+      ">>>>>>> refs/heads/B",
+    ];
+
+    let res = conflicted_lines(str_lines);
+
+    assert_eq!(res.ref_name_base, "1234567");
+    assert_eq!(
+      res.sections[0].base,
+      Some(vec![CFSectionLine::Blank(BlankLine { section: 0 })])
+    );
+  }
 }
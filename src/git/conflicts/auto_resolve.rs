@@ -0,0 +1,265 @@
+use std::path::Path;
+
+use serde::Serialize;
+use ts_rs::TS;
+
+use crate::git::conflicts::api::parse_conflicted_file;
+use crate::git::conflicts::conflicted_file::{CFSection, CFSide};
+use crate::git::conflicts::file_text::{read_file_text, write_file_text};
+use crate::git::conflicts::resolve::{build_resolved_text, SectionChoice};
+use crate::git::queries::wip::wip_patches::load_wip_patches;
+use crate::server::git_request::ReqOptions;
+use crate::server::request_util::R;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, TS)]
+#[ts(export)]
+pub enum AutoResolveReason {
+  // Also covers sides that only differed in line endings, as those aren't kept per line.
+  IdenticalSides,
+  // The other side is the same as the base, so only this side changed.
+  OnlyLeftChanged,
+  OnlyRightChanged,
+  // The other side only changed whitespace from the base, like
+  // "merge -Xignore-space-change". Needs a base to tell which side that was.
+  WhitespaceOnly,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct AutoResolvedSection {
+  pub section: usize,
+  pub reason: AutoResolveReason,
+  pub choice: SectionChoice,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct AutoResolvedFile {
+  pub path: String,
+  pub resolved: Vec<AutoResolvedSection>,
+  // Indices of sections still conflicted, in the file as it was before.
+  pub remaining: Vec<usize>,
+}
+
+/*
+Resolves the trivial sections of every conflicted file and writes the files back with the
+rest of the conflicts left in. Nothing is staged, so the results can be reviewed first.
+ */
+pub fn auto_resolve_conflicts(options: &ReqOptions) -> R<Vec<AutoResolvedFile>> {
+  let patches = load_wip_patches(options)?;
+  let mut files = Vec::new();
+
+  for patch in patches.patches.iter().filter(|p| p.conflicted) {
+    let path = Path::new(&options.repo_path).join(&patch.new_file);
+    let Ok(file_text) = read_file_text(&path) else {
+      // Deleted on one side. Nothing we can do here.
+      continue;
+    };
+
+    let file = parse_conflicted_file(&file_text.text);
+    let mut choices = Vec::new();
+    let mut resolved = Vec::new();
+    let mut remaining = Vec::new();
+
+    for (i, section) in file.sections.iter().enumerate() {
+      match choose_trivial_side(section) {
+        Some((choice, reason)) => {
+          resolved.push(AutoResolvedSection {
+            section: i,
+            reason,
+            choice: choice.clone(),
+          });
+          choices.push(Some(choice));
+        }
+        None => {
+          remaining.push(i);
+          choices.push(None);
+        }
+      }
+    }
+
+    if !resolved.is_empty() {
+      match build_resolved_text(&file_text.text, &choices) {
+        Ok(text) => write_file_text(&path, &text, &file_text)?,
+        Err(_) => {
+          // E.g. an unclosed marker. Left for the user without stopping the rest.
+          resolved.clear();
+          remaining = (0..file.sections.len()).collect();
+        }
+      }
+    }
+
+    files.push(AutoResolvedFile {
+      path: patch.new_file.clone(),
+      resolved,
+      remaining,
+    });
+  }
+
+  Ok(files)
+}
+
+fn choose_trivial_side(
+  section: &CFSection,
+) -> Option<(SectionChoice, AutoResolveReason)> {
+  use AutoResolveReason::*;
+
+  let left = section.texts(&CFSide::Left);
+  let right = section.texts(&CFSide::Right);
+  let base = section.base.as_ref().map(|_| section.texts(&CFSide::Base));
+
+  if left == right {
+    return Some((SectionChoice::Left, IdenticalSides));
+  }
+
+  let base = base?;
+
+  if base == left {
+    return Some((SectionChoice::Right, OnlyRightChanged));
+  }
+  if base == right {
+    return Some((SectionChoice::Left, OnlyLeftChanged));
+  }
+
+  // If both sides changed more than whitespace, neither can be dropped.
+  let base = normalise_whitespace(&base);
+  let left_changed = normalise_whitespace(&left) != base;
+  let right_changed = normalise_whitespace(&right) != base;
+
+  match (left_changed, right_changed) {
+    (true, false) => Some((SectionChoice::Left, WhitespaceOnly)),
+    (false, true) => Some((SectionChoice::Right, WhitespaceOnly)),
+    _ => None,
+  }
+}
+
+// Whitespace runs become a single space. Blank lines are kept, as adding one is a change.
+fn normalise_whitespace(lines: &[&str]) -> Vec<String> {
+  lines
+    .iter()
+    .map(|l| l.split_whitespace().collect::<Vec<_>>().join(" "))
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use std::fs;
+  use std::process::Command;
+
+  use crate::git::conflicts::api::parse_conflicted_file;
+  use crate::git::conflicts::auto_resolve::{
+    auto_resolve_conflicts, choose_trivial_side, AutoResolveReason,
+  };
+  use crate::git::conflicts::resolve::SectionChoice;
+  use crate::git::test_repo::TestRepo;
+  use crate::server::git_request::ReqOptions;
+
+  fn choose(text: &str) -> Option<(SectionChoice, AutoResolveReason)> {
+    let file = parse_conflicted_file(text);
+
+    choose_trivial_side(&file.sections[0])
+  }
+
+  #[test]
+  fn test_choose_trivial_side() {
+    use AutoResolveReason::*;
+
+    let identical = choose("<<<<<<< HEAD\na\n=======\na\n>>>>>>> B\n");
+    assert!(matches!(
+      identical,
+      Some((SectionChoice::Left, IdenticalSides))
+    ));
+
+    let only_right = choose("<<<<<<< HEAD\na\n||||||| base\na\n=======\nb\n>>>>>>> B\n");
+    assert!(matches!(
+      only_right,
+      Some((SectionChoice::Right, OnlyRightChanged))
+    ));
+
+    let only_left = choose("<<<<<<< HEAD\nb\n||||||| base\na\n=======\na\n>>>>>>> B\n");
+    assert!(matches!(
+      only_left,
+      Some((SectionChoice::Left, OnlyLeftChanged))
+    ));
+
+    // Without a base we can't tell which side only changed whitespace.
+    assert!(choose("<<<<<<< HEAD\n  a  b\n=======\na b\n>>>>>>> B\n").is_none());
+
+    let left_reindented =
+      choose("<<<<<<< HEAD\n    a\n||||||| base\n  a\n=======\n  b\n>>>>>>> B\n");
+    assert!(matches!(
+      left_reindented,
+      Some((SectionChoice::Right, WhitespaceOnly))
+    ));
+
+    let right_reindented =
+      choose("<<<<<<< HEAD\n  b\n||||||| base\n  a\n=======\n    a\n>>>>>>> B\n");
+    assert!(matches!(
+      right_reindented,
+      Some((SectionChoice::Left, WhitespaceOnly))
+    ));
+
+    // Both only changed whitespace, differently.
+    assert!(
+      choose("<<<<<<< HEAD\n    a\n||||||| base\n  a\n=======\n\ta\n>>>>>>> B\n")
+        .is_none()
+    );
+    // Both changed the text, differing only in indentation.
+    assert!(
+      choose("<<<<<<< HEAD\n    b\n||||||| base\n  a\n=======\n  b\n>>>>>>> B\n")
+        .is_none()
+    );
+
+    assert!(choose("<<<<<<< HEAD\na\n=======\nb\n>>>>>>> B\n").is_none());
+    assert!(
+      choose("<<<<<<< HEAD\na\n||||||| base\nc\n=======\nb\n>>>>>>> B\n").is_none()
+    );
+  }
+
+  #[test]
+  fn test_auto_resolve_keeps_unclosed_file() {
+    let repo = TestRepo::new("auto_resolve_keeps_unclosed_file");
+    repo.write("a.txt", "a\n");
+    repo.write("b.txt", "b\n");
+    repo.commit("base");
+    repo.git(&["checkout", "-q", "-b", "other"]);
+    repo.write("a.txt", "a2\n");
+    repo.write("b.txt", "b2\n");
+    repo.commit("other");
+    repo.git(&["checkout", "-q", "main"]);
+    repo.write("a.txt", "a1\n");
+    repo.write("b.txt", "b1\n");
+    repo.commit("main");
+
+    // Fails with conflicts in both files.
+    let merge = Command::new("git")
+      .args(["merge", "-q", "other"])
+      .current_dir(&repo.path)
+      .output()
+      .unwrap();
+    assert!(!merge.status.success());
+
+    let unclosed = "<<<<<<< HEAD\na\n=======\na\n>>>>>>> other\n<<<<<<< HEAD\na1\n";
+    repo.write("a.txt", unclosed);
+    repo.write("b.txt", "<<<<<<< HEAD\nb\n=======\nb\n>>>>>>> other\n");
+
+    let mut files = auto_resolve_conflicts(&ReqOptions {
+      repo_path: repo.path_str(),
+    })
+    .unwrap();
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+
+    assert_eq!(files.len(), 2);
+    assert!(files[0].resolved.is_empty());
+    assert!(!files[0].remaining.is_empty());
+    assert_eq!(
+      fs::read_to_string(repo.path.join("a.txt")).unwrap(),
+      unclosed
+    );
+
+    assert_eq!(files[1].resolved.len(), 1);
+    assert_eq!(fs::read_to_string(repo.path.join("b.txt")).unwrap(), "b\n");
+  }
+}
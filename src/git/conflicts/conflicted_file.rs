@@ -11,8 +11,16 @@ pub struct ConflictedFile {
   pub sections: Vec<CFSection>,
   pub ref_name_top: String,
   pub ref_name_bottom: String,
+  // Label after the ||||||| marker with diff3 and zdiff3 styles. Empty otherwise.
+  pub ref_name_base: String,
   pub line_ending: String,
+  // As named by encoding_rs, e.g. "UTF-8" or "windows-1252".
+  pub encoding: String,
+  pub bom: bool,
   pub max_line_length: usize,
+  pub trailing_newline: bool,
+  // Of the text parsed. Resolving checks the file hasn't changed since.
+  pub text_hash: String,
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Serialize, TS)]
@@ -66,8 +74,13 @@ impl ConflictedFile {
       sections: vec![],
       ref_name_top: String::new(),
       ref_name_bottom: String::new(),
+      ref_name_base: String::new(),
       line_ending: String::from("\n"),
+      encoding: String::from("UTF-8"),
+      bom: false,
       max_line_length: 0,
+      trailing_newline: true,
+      text_hash: String::new(),
     }
   }
 }
@@ -77,20 +90,44 @@ impl ConflictedFile {
 pub struct CFSection {
   pub left: Vec<CFSectionLine>,
   pub right: Vec<CFSectionLine>,
+  // Only with diff3 and zdiff3 conflict styles.
+  pub base: Option<Vec<CFSectionLine>>,
 }
 
 impl CFSection {
-  pub fn get(&self, side: &CFSide) -> &Vec<CFSectionLine> {
+  pub fn new() -> Self {
+    Self {
+      left: vec![],
+      right: vec![],
+      base: None,
+    }
+  }
+
+  pub fn get(&self, side: &CFSide) -> &[CFSectionLine] {
     match side {
       CFSide::Left => &self.left,
       CFSide::Right => &self.right,
+      CFSide::Base => self.base.as_deref().unwrap_or(&[]),
     }
   }
 
+  // Text of the side's lines, without the blanks added for balancing.
+  pub fn texts(&self, side: &CFSide) -> Vec<&str> {
+    self
+      .get(side)
+      .iter()
+      .filter_map(|line| match line {
+        CFSectionLine::Conflict(c) => Some(c.text.as_str()),
+        CFSectionLine::Blank(_) => None,
+      })
+      .collect()
+  }
+
   pub fn get_mut(&mut self, side: &CFSide) -> &mut Vec<CFSectionLine> {
     match side {
       CFSide::Left => &mut self.left,
       CFSide::Right => &mut self.right,
+      CFSide::Base => self.base.get_or_insert_with(Vec::new),
     }
   }
 }
@@ -101,6 +138,7 @@ impl CFSection {
 pub enum CFSide {
   Left,
   Right,
+  Base,
 }
 
 impl fmt::Display for CFSide {
@@ -108,6 +146,7 @@ impl fmt::Display for CFSide {
     match *self {
       CFSide::Left => write!(f, "Left"),
       CFSide::Right => write!(f, "Right"),
+      CFSide::Base => write!(f, "Base"),
     }
   }
 }
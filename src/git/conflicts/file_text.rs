@@ -0,0 +1,110 @@
+use std::fs;
+use std::path::Path;
+
+use encoding_rs::{Encoding, UTF_16BE, UTF_16LE, UTF_8};
+
+use crate::f;
+use crate::server::request_util::{ES, R};
+use crate::util::text_encoding::{decode_text, DecodedText};
+
+// A conflicted file's text with what's needed to write it back as it was.
+pub struct FileText {
+  // Without the BOM.
+  pub text: String,
+  pub encoding: &'static Encoding,
+  pub bom: bool,
+}
+
+pub fn read_file_text(path: &Path) -> R<FileText> {
+  Ok(decode_file_text(&fs::read(path)?))
+}
+
+pub fn write_file_text(path: &Path, text: &str, file: &FileText) -> R<()> {
+  fs::write(path, encode_file_text(text, file.encoding, file.bom)?)?;
+
+  Ok(())
+}
+
+fn decode_file_text(bytes: &[u8]) -> FileText {
+  let DecodedText {
+    text,
+    encoding,
+    bom,
+  } = decode_text(bytes, None);
+
+  FileText {
+    text,
+    encoding,
+    bom,
+  }
+}
+
+// encoding_rs only encodes UTF-16 as UTF-8, so we do that ourselves.
+fn encode_file_text(text: &str, encoding: &'static Encoding, bom: bool) -> R<Vec<u8>> {
+  let mut bytes = Vec::new();
+
+  if encoding == UTF_16LE || encoding == UTF_16BE {
+    let little_endian = encoding == UTF_16LE;
+    let units = if bom {
+      vec!['\u{feff}' as u16]
+    } else {
+      Vec::new()
+    };
+
+    for unit in units.into_iter().chain(text.encode_utf16()) {
+      if little_endian {
+        bytes.extend(unit.to_le_bytes());
+      } else {
+        bytes.extend(unit.to_be_bytes());
+      }
+    }
+
+    return Ok(bytes);
+  }
+
+  if bom && encoding == UTF_8 {
+    bytes.extend(b"\xEF\xBB\xBF");
+  }
+
+  let (encoded, _, had_errors) = encoding.encode(text);
+
+  // It would be written with HTML entities in place of the characters.
+  if had_errors {
+    return Err(ES::from(&f!(
+      "Text contains characters that can't be written as {}",
+      encoding.name()
+    )));
+  }
+
+  bytes.extend(encoded.iter());
+
+  Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::git::conflicts::file_text::{decode_file_text, encode_file_text};
+
+  #[test]
+  fn test_file_text_round_trip() {
+    let files: [&[u8]; 4] = [
+      b"a\r\nb\r\n",
+      b"\xEF\xBB\xBFcaf\xC3\xA9\n",
+      b"\xFF\xFEa\x00\r\x00\n\x00",
+      b"caf\xE9 cr\xE8me br\xFBl\xE9e\n",
+    ];
+
+    for bytes in files {
+      let file = decode_file_text(bytes);
+
+      assert_eq!(
+        encode_file_text(&file.text, file.encoding, file.bom).unwrap(),
+        bytes
+      );
+    }
+
+    let latin = decode_file_text(b"caf\xE9 cr\xE8me br\xFBl\xE9e\n");
+    assert_eq!(latin.text, "café crème brûlée\n");
+    assert!(encode_file_text("\u{1F600}", latin.encoding, false).is_err());
+  }
+}
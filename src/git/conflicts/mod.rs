@@ -1,2 +1,5 @@
 pub(crate) mod api;
+pub(crate) mod auto_resolve;
 pub(crate) mod conflicted_file;
+pub(crate) mod file_text;
+pub(crate) mod resolve;
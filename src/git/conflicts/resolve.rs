@@ -0,0 +1,238 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::f;
+use crate::git::conflicts::api::{
+  hash_text, CONFLICT_BASE, CONFLICT_END, CONFLICT_MIDDLE, CONFLICT_START,
+};
+use crate::git::conflicts::conflicted_file::CFSide;
+use crate::git::conflicts::file_text::{read_file_text, write_file_text};
+use crate::git::git_types::WipPatch;
+use crate::git::run_git::{run_git_err, RunGitOptions};
+use crate::server::request_util::{ES, R};
+
+#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub enum SectionChoice {
+  Left,
+  Right,
+  // Only with diff3 and zdiff3 conflict styles.
+  Base,
+  LeftThenRight,
+  RightThenLeft,
+  // Replaces the whole section. Line endings are converted to the file's.
+  Custom { text: String },
+}
+
+#[derive(Debug, Deserialize, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct ReqResolveConflictOptions {
+  pub repo_path: String,
+  pub patch: WipPatch,
+  // One per section, in order.
+  pub choices: Vec<SectionChoice>,
+  // From the ConflictedFile the choices were made on.
+  pub text_hash: String,
+  // git add the file after writing it.
+  pub stage: bool,
+}
+
+pub fn resolve_conflicted_file(options: &ReqResolveConflictOptions) -> R<()> {
+  let ReqResolveConflictOptions {
+    repo_path,
+    patch,
+    choices,
+    text_hash,
+    stage,
+  } = options;
+
+  let path = Path::new(repo_path).join(&patch.new_file);
+  let file = read_file_text(&path)?;
+
+  if hash_text(&file.text) != *text_hash {
+    return Err(ES::from(
+      "File has changed since it was loaded. Reload it and try again.",
+    ));
+  }
+
+  let choices: Vec<Option<SectionChoice>> = choices.iter().cloned().map(Some).collect();
+  let resolved = build_resolved_text(&file.text, &choices)?;
+
+  write_file_text(&path, &resolved, &file)?;
+
+  if *stage {
+    let out = run_git_err(RunGitOptions {
+      repo_path,
+      args: ["add", "--", &patch.new_file],
+    })?;
+
+    // Warnings about line endings also go to stderr.
+    if out.stderr.contains("fatal:") {
+      return Err(ES::from(out.stderr.trim()));
+    }
+  }
+
+  Ok(())
+}
+
+#[derive(Default)]
+struct RawSection<'a> {
+  // Every line from the start marker to the end marker, for leaving it unresolved.
+  all: Vec<&'a str>,
+  left: Vec<&'a str>,
+  right: Vec<&'a str>,
+  base: Vec<&'a str>,
+}
+
+/*
+Works on the raw text rather than a ConflictedFile so lines keep their own endings.
+Sections with a None choice are left as they are, markers included. Sections are counted
+the same way as in conflicted_lines.
+ */
+pub fn build_resolved_text(text: &str, choices: &[Option<SectionChoice>]) -> R<String> {
+  let mut resolved = String::new();
+  let mut section: Option<RawSection> = None;
+  let mut side = CFSide::Left;
+  let mut num_sections = 0;
+
+  for line in text.split_inclusive('\n') {
+    let Some(current) = section.as_mut() else {
+      if line.starts_with(CONFLICT_START) {
+        section = Some(RawSection {
+          all: vec![line],
+          ..RawSection::default()
+        });
+        side = CFSide::Left;
+      } else {
+        resolved += line;
+      }
+      continue;
+    };
+
+    current.all.push(line);
+
+    if line.starts_with(CONFLICT_BASE) {
+      side = CFSide::Base;
+    } else if line.starts_with(CONFLICT_MIDDLE) {
+      side = CFSide::Right;
+    } else if line.starts_with(CONFLICT_END) {
+      let choice = choices
+        .get(num_sections)
+        .ok_or_else(|| ES::from(&f!("Missing a choice for section {}", num_sections)))?;
+      let line_ending = if current.all[0].ends_with("\r\n") {
+        "\r\n"
+      } else {
+        "\n"
+      };
+
+      resolved += &match choice {
+        Some(choice) => section_text(current, choice, line_ending),
+        None => current.all.concat(),
+      };
+      section = None;
+      num_sections += 1;
+    } else {
+      match side {
+        CFSide::Left => current.left.push(line),
+        CFSide::Right => current.right.push(line),
+        CFSide::Base => current.base.push(line),
+      }
+    }
+  }
+
+  if section.is_some() {
+    return Err(ES::from(&f!(
+      "Conflict section {} isn't closed",
+      num_sections
+    )));
+  }
+
+  if num_sections != choices.len() {
+    return Err(ES::from(&f!(
+      "Expected {} choices, got {}",
+      num_sections,
+      choices.len()
+    )));
+  }
+
+  Ok(resolved)
+}
+
+fn section_text(
+  section: &RawSection,
+  choice: &SectionChoice,
+  line_ending: &str,
+) -> String {
+  let RawSection {
+    left, right, base, ..
+  } = section;
+
+  match choice {
+    SectionChoice::Left => left.concat(),
+    SectionChoice::Right => right.concat(),
+    SectionChoice::Base => base.concat(),
+    SectionChoice::LeftThenRight => left.concat() + &right.concat(),
+    SectionChoice::RightThenLeft => right.concat() + &left.concat(),
+    SectionChoice::Custom { text } => {
+      text.lines().map(|l| f!("{}{}", l, line_ending)).collect()
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::git::conflicts::resolve::{build_resolved_text, SectionChoice};
+
+  #[test]
+  fn test_build_resolved_text() {
+    let text = "a\r\n<<<<<<< HEAD\r\nb\r\n=======\r\nc\r\nd\r\n>>>>>>> B\r\ne\r\n\
+      <<<<<<< HEAD\r\nf\r\n=======\r\ng\r\n>>>>>>> B\r\n";
+
+    let resolved = build_resolved_text(
+      text,
+      &[
+        Some(SectionChoice::RightThenLeft),
+        Some(SectionChoice::Custom {
+          text: "x\ny".to_string(),
+        }),
+      ],
+    )
+    .unwrap();
+
+    assert_eq!(resolved, "a\r\nc\r\nd\r\nb\r\ne\r\nx\r\ny\r\n");
+
+    let resolved = build_resolved_text(
+      text,
+      &[Some(SectionChoice::Left), Some(SectionChoice::Right)],
+    )
+    .unwrap();
+    assert_eq!(resolved, "a\r\nb\r\ne\r\ng\r\n");
+
+    let resolved =
+      build_resolved_text(text, &[None, Some(SectionChoice::Right)]).unwrap();
+    assert_eq!(
+      resolved,
+      "a\r\n<<<<<<< HEAD\r\nb\r\n=======\r\nc\r\nd\r\n>>>>>>> B\r\ne\r\ng\r\n"
+    );
+
+    assert!(build_resolved_text(text, &[Some(SectionChoice::Left)]).is_err());
+  }
+
+  #[test]
+  fn test_resolve_diff3_base() {
+    let text = "<<<<<<< HEAD\nb\n||||||| base\na\n=======\nc\n>>>>>>> B\nend";
+
+    assert_eq!(
+      build_resolved_text(text, &[Some(SectionChoice::Base)]).unwrap(),
+      "a\nend"
+    );
+    assert_eq!(
+      build_resolved_text(text, &[Some(SectionChoice::LeftThenRight)]).unwrap(),
+      "b\nc\nend"
+    );
+  }
+}
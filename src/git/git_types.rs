@@ -37,6 +37,7 @@ pub struct CommitInfo {
   pub message: String,
   pub stash_id: String,
   pub refs: Vec<RefInfo>,
+  pub signature: Option<CommitSignature>,
 
   pub filtered: bool,
   pub num_skipped: u32,
@@ -56,11 +57,36 @@ pub struct Commit {
   pub message: String,
   pub stash_id: String,
   pub refs: Vec<String>,
+  pub signature: Option<CommitSignature>,
 
   pub filtered: bool,
   pub num_skipped: u32,
 }
 
+// Only loaded when requested, as verifying signatures is slow.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct CommitSignature {
+  pub status: SignatureStatus,
+  pub signer: String,
+  pub key_id: String,
+}
+
+// See %G? in https://git-scm.com/docs/pretty-formats
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub enum SignatureStatus {
+  Good,
+  // Good signature, but we don't know whether the key is trusted.
+  Untrusted,
+  Bad,
+  UnknownKey,
+  Expired,
+  Revoked,
+  None,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
 #[serde(rename_all = "camelCase")]
 #[ts(export)]
@@ -144,6 +170,56 @@ pub struct HunkLine {
   pub text: String,
   pub index: u32,
   pub line_ending: String,
+  // Parts of the text that changed, for removed lines paired with added lines.
+  #[serde(default)]
+  pub changed_ranges: Vec<CharRange>,
+  // Set when this line was moved rather than edited.
+  #[serde(default)]
+  pub moved: Option<MovedLine>,
+  // Whitespace errors and hidden characters.
+  #[serde(default)]
+  pub warnings: Vec<LineWarning>,
+}
+
+// Where a moved line came from (for added lines) or went to (for removed lines).
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct MovedLine {
+  // Lines moved together as a block share a group.
+  pub group: u32,
+  pub file: String,
+  pub line_num: i32,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct LineWarning {
+  pub kind: LineWarningKind,
+  // Empty for CrLf, as the line ending isn't part of the text.
+  pub range: CharRange,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub enum LineWarningKind {
+  // These follow core.whitespace and are only checked on added lines.
+  TrailingWhitespace,
+  SpaceBeforeTab,
+  IndentWithNonTab,
+  TabInIndent,
+  CrLf,
+  // Could change how the code reads without being seen ("Trojan Source").
+  BidiControl,
+  ZeroWidth,
+}
+
+// Char (not byte) offsets into a line, end exclusive.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct CharRange {
+  pub start: usize,
+  pub end: usize,
 }
 
 #[derive(PartialEq, Eq, Debug, Clone, Serialize, Deserialize, TS)]
@@ -198,4 +274,7 @@ pub struct WipPatch {
   pub conflicted: bool,
   pub id: String,
   pub is_image: bool,
+  // Marked with "-diff" or "binary" in .gitattributes.
+  #[serde(default)]
+  pub is_binary: bool,
 }
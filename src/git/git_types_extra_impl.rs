@@ -1,10 +1,8 @@
 use crate::git::git_types::{
-  Hunk, HunkLine, HunkRange, Patch, PatchType, RefLocation, WipPatchType,
+  Hunk, HunkLine, HunkRange, Patch, PatchType, RefLocation, WipPatch, WipPatchType,
 };
-use std::ffi::OsStr;
 use std::fmt;
 use std::fmt::Formatter;
-use std::path::Path;
 
 impl fmt::Display for PatchType {
   fn fmt(&self, f: &mut Formatter) -> fmt::Result {
@@ -79,17 +77,22 @@ impl HunkRange {
 }
 
 impl Patch {
-  pub fn get_file_extension(&self) -> String {
-    let file_name = if self.new_file.is_empty() {
-      &self.old_file
-    } else {
-      &self.new_file
-    };
+  // The new path, unless the file was deleted.
+  pub fn get_file_path(&self) -> &str {
+    file_path(&self.old_file, &self.new_file)
+  }
+}
+
+impl WipPatch {
+  pub fn get_file_path(&self) -> &str {
+    file_path(&self.old_file, &self.new_file)
+  }
+}
 
-    Path::new(file_name)
-      .extension()
-      .and_then(OsStr::to_str)
-      .unwrap_or("")
-      .to_string()
+fn file_path<'a>(old_file: &'a str, new_file: &'a str) -> &'a str {
+  if new_file.is_empty() {
+    old_file
+  } else {
+    new_file
   }
 }
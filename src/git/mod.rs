@@ -9,3 +9,5 @@ pub(crate) mod queries;
 pub(crate) mod run_git;
 pub(crate) mod run_git_action;
 pub(crate) mod store;
+#[cfg(test)]
+pub(crate) mod test_repo;
@@ -0,0 +1,411 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::f;
+use crate::git::git_types::{Commit, DateResult};
+use crate::git::queries::config::read_config_value;
+use crate::git::queries::hunks::html_code::{build_line, div, escape_xml, pad_left};
+use crate::git::queries::syntax_colouring::{ColourOutput, ThemeColour, COLOURING};
+use crate::git::run_git::{run_git_err, RunGitOptions};
+use crate::git::store::STORE;
+use crate::server::request_util::{ES, R};
+
+#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct ReqBlameOptions {
+  pub repo_path: String,
+  // None blames the working tree version of the file.
+  pub commit_id: Option<String>,
+  pub file_path: String,
+  // -w
+  pub ignore_whitespace: bool,
+  // -M
+  pub detect_moved: bool,
+  // -C
+  pub detect_copied: bool,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct Blame {
+  pub groups: Vec<BlameGroup>,
+  // Keyed by commit id.
+  pub commits: HashMap<String, BlameCommit>,
+}
+
+// A run of lines last changed by the same commit.
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct BlameGroup {
+  pub commit_id: String,
+  // 1 based line number in the blamed file.
+  pub start_line: u32,
+  // Line number and path in the commit that introduced these lines.
+  pub orig_start_line: u32,
+  pub orig_path: String,
+  pub lines: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct BlameCommit {
+  pub id: String,
+  pub author: String,
+  pub email: String,
+  pub date: DateResult,
+  pub summary: String,
+  // No more history before this commit, or it's outside the requested range.
+  pub boundary: bool,
+  // Not committed yet (working tree changes).
+  pub uncommitted: bool,
+  // Filled in when the commit has already been loaded.
+  pub commit: Option<Commit>,
+}
+
+const UNCOMMITTED_ID: &str = "0000000000000000000000000000000000000000";
+
+pub fn load_blame(options: &ReqBlameOptions) -> R<Blame> {
+  let out = run_git_err(RunGitOptions {
+    repo_path: &options.repo_path,
+    args: blame_args(options)?,
+  })?;
+
+  // E.g. the file doesn't exist at this commit.
+  if out.stdout.is_empty() && !out.stderr.is_empty() {
+    return Err(ES::from(out.stderr.trim()));
+  }
+
+  let mut blame = parse_blame_porcelain(&out.stdout)?;
+
+  if let Some((commits, _)) = STORE.get_commits_and_refs(&options.repo_path) {
+    for c in commits {
+      if let Some(info) = blame.commits.get_mut(&c.id) {
+        info.commit = Some(c);
+      }
+    }
+  }
+
+  Ok(blame)
+}
+
+fn blame_args(options: &ReqBlameOptions) -> R<Vec<String>> {
+  let ReqBlameOptions {
+    repo_path,
+    commit_id,
+    file_path,
+    ignore_whitespace,
+    detect_moved,
+    detect_copied,
+  } = options;
+
+  let mut args = vec![f!("blame"), f!("--porcelain")];
+
+  if *ignore_whitespace {
+    args.push(f!("-w"));
+  }
+  if *detect_moved {
+    args.push(f!("-M"));
+  }
+  if *detect_copied {
+    args.push(f!("-C"));
+  }
+
+  // Git applies blame.ignoreRevsFile itself. Check it here so a missing file gives a
+  // clearer error than "could not open object name list".
+  if let Some(revs_file) = read_config_value(repo_path, "blame.ignoreRevsFile") {
+    if !revs_file.is_empty() && !Path::new(repo_path).join(&revs_file).exists() {
+      return Err(ES::from(&f!(
+        "blame.ignoreRevsFile is set to \"{}\", but the file doesn't exist",
+        revs_file
+      )));
+    }
+  }
+
+  if let Some(commit_id) = commit_id {
+    args.push(commit_id.clone());
+  }
+
+  args.push(f!("--"));
+  args.push(file_path.clone());
+
+  Ok(args)
+}
+
+/*
+Porcelain output looks like this. Commit info is only included the first time a commit
+is seen. A header line with 4 parts starts a new group.
+
+b8aff6b055542fe5751e2515d09f0608f972cfff 1 1 2
+author A
+author-mail <a@a.com>
+author-time 1792365235
+author-tz +0000
+summary one
+boundary
+filename f.rs
+  a
+b8aff6b055542fe5751e2515d09f0608f972cfff 2 2
+  b
+ */
+fn parse_blame_porcelain(text: &str) -> R<Blame> {
+  let mut groups: Vec<BlameGroup> = Vec::new();
+  let mut commits: HashMap<String, BlameCommit> = HashMap::new();
+  let mut current_id = String::new();
+
+  for line in text.lines() {
+    if let Some(content) = line.strip_prefix('\t') {
+      if let Some(group) = groups.last_mut() {
+        group.lines.push(content.to_string());
+      }
+      continue;
+    }
+
+    let info = commits.get_mut(&current_id);
+
+    if let (Some(info), Some((key, value))) = (info, line.split_once(' ')) {
+      match key {
+        "author" => info.author = value.to_string(),
+        "author-mail" => {
+          info.email = value
+            .trim_start_matches('<')
+            .trim_end_matches('>')
+            .to_string()
+        }
+        "author-time" => info.date.ms = value.parse::<usize>().unwrap_or_default() * 1000,
+        "author-tz" => info.date.adjustment = value.parse().unwrap_or_default(),
+        "summary" => info.summary = value.to_string(),
+        "filename" => {
+          if let Some(group) = groups.last_mut() {
+            group.orig_path = value.to_string();
+          }
+        }
+        _ => {
+          if let Some(group) = parse_group_header(line) {
+            current_id = start_group(group, &mut groups, &mut commits);
+          }
+        }
+      }
+    } else if line == "boundary" {
+      if let Some(info) = commits.get_mut(&current_id) {
+        info.boundary = true;
+      }
+    } else if let Some(group) = parse_group_header(line) {
+      current_id = start_group(group, &mut groups, &mut commits);
+    } else if !line.is_empty() {
+      return Err(ES::from(&f!("Unexpected blame output: {}", line)));
+    }
+  }
+
+  Ok(Blame { groups, commits })
+}
+
+// Returns the commit id.
+fn start_group(
+  group: Option<BlameGroup>,
+  groups: &mut Vec<BlameGroup>,
+  commits: &mut HashMap<String, BlameCommit>,
+) -> String {
+  let Some(mut group) = group else {
+    // Continuation of the current group.
+    return groups
+      .last()
+      .map(|g| g.commit_id.clone())
+      .unwrap_or_default();
+  };
+
+  let id = group.commit_id.clone();
+
+  commits.entry(id.clone()).or_insert_with(|| BlameCommit {
+    id: id.clone(),
+    author: String::new(),
+    email: String::new(),
+    date: DateResult {
+      ms: 0,
+      adjustment: 0,
+    },
+    summary: String::new(),
+    boundary: false,
+    uncommitted: id == UNCOMMITTED_ID,
+    commit: None,
+  });
+
+  // Later groups of the same commit don't repeat the filename.
+  if let Some(prev) = groups.iter().rev().find(|g| g.commit_id == id) {
+    group.orig_path = prev.orig_path.clone();
+  }
+
+  groups.push(group);
+
+  id
+}
+
+// "<id> <orig line> <final line> <num lines>", num lines is missing for continuations.
+// Returns Some(None) for a continuation.
+fn parse_group_header(line: &str) -> Option<Option<BlameGroup>> {
+  let parts: Vec<&str> = line.split(' ').collect();
+
+  if parts.len() < 3 || parts.len() > 4 || parts[0].len() < 40 {
+    return None;
+  }
+  if !parts[0].chars().all(|c| c.is_ascii_hexdigit()) {
+    return None;
+  }
+
+  let orig_start_line = parts[1].parse::<u32>().ok()?;
+  let start_line = parts[2].parse::<u32>().ok()?;
+
+  if parts.len() == 3 {
+    return Some(None);
+  }
+
+  Some(Some(BlameGroup {
+    commit_id: parts[0].to_string(),
+    start_line,
+    orig_start_line,
+    orig_path: String::new(),
+    lines: Vec::new(),
+  }))
+}
+
+#[derive(Debug, Deserialize, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct ReqBlameHtmlOptions {
+  pub blame: ReqBlameOptions,
+  pub theme: ThemeColour,
+  #[serde(default)]
+  pub output: ColourOutput,
+}
+
+pub fn get_blame_as_html(options: &ReqBlameHtmlOptions) -> R<String> {
+  let ReqBlameHtmlOptions {
+    blame,
+    theme,
+    output,
+  } = options;
+
+  let Blame { groups, commits } = load_blame(blame)?;
+
+  let first_line = groups
+    .first()
+    .and_then(|g| g.lines.first())
+    .map(|l| l.as_str());
+
+  let mut colouring = COLOURING.write()?;
+  let mut c = colouring.get_colour_line_for_file(
+    theme,
+    &blame.repo_path,
+    &blame.file_path,
+    first_line,
+  );
+  c.output = *output;
+
+  let num_lines: usize = groups.iter().map(|g| g.lines.len()).sum();
+  let num_width = num_lines.to_string().len();
+
+  let mut margin = String::new();
+  let mut lines = String::new();
+
+  for group in &groups {
+    let info = commits.get(&group.commit_id);
+
+    for (i, line) in group.lines.iter().enumerate() {
+      let line_num = pad_left((group.start_line as usize + i).to_string(), num_width);
+
+      // Only label the first line of each group.
+      if i == 0 {
+        margin += &div("blameStart", &f!("{} {}", group_label(info), line_num));
+      } else {
+        margin += &div("blame", &line_num);
+      }
+
+      // Unlike a diff we have the whole file, so we don't need to fake up context.
+      let text = if let Ok(parts) = c.colour(&f!("{}\n", line)) {
+        build_line(parts, &c, &[], &[])
+      } else {
+        escape_xml(line)
+      };
+
+      lines += &div("none", &text);
+    }
+  }
+
+  // language=HTML
+  Ok(f!(
+    "<div class='margin'>{}</div><div class='code'>{}</div>",
+    margin,
+    lines
+  ))
+}
+
+fn group_label(info: Option<&BlameCommit>) -> String {
+  match info {
+    Some(info) if info.uncommitted => f!("{:<7} {}", "", escape_xml("Not committed")),
+    Some(info) => f!("{:.7} {}", info.id, escape_xml(&info.author)),
+    None => String::new(),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::git::queries::blame::parse_blame_porcelain;
+
+  #[test]
+  fn test_parse_blame_porcelain() {
+    let out = "b8aff6b055542fe5751e2515d09f0608f972cfff 1 1 1
+author A
+author-mail <a@a.com>
+author-time 1792365235
+author-tz +1300
+committer A
+committer-mail <a@a.com>
+committer-time 1792365235
+committer-tz +1300
+summary one
+boundary
+filename old.rs
+\ta
+0287ca340e124b6b0fe08442a6c105e15fe015a2 2 2 2
+author B
+author-mail <b@b.com>
+author-time 1792365236
+author-tz +0000
+committer B
+committer-mail <b@b.com>
+committer-time 1792365236
+committer-tz +0000
+summary two
+previous b8aff6b055542fe5751e2515d09f0608f972cfff old.rs
+filename f.rs
+\tB
+0287ca340e124b6b0fe08442a6c105e15fe015a2 3 3
+\tC
+b8aff6b055542fe5751e2515d09f0608f972cfff 3 4 1
+\td
+";
+
+    let blame = parse_blame_porcelain(out).unwrap();
+
+    assert_eq!(blame.groups.len(), 3);
+    assert_eq!(blame.groups[1].lines, vec!["B", "C"]);
+    assert_eq!(blame.groups[2].start_line, 4);
+    assert_eq!(blame.groups[2].orig_path, "old.rs");
+
+    let a = &blame.commits["b8aff6b055542fe5751e2515d09f0608f972cfff"];
+    assert_eq!(a.email, "a@a.com");
+    assert_eq!(a.date.ms, 1792365235000);
+    assert_eq!(a.date.adjustment, 1300);
+    assert!(a.boundary);
+
+    let b = &blame.commits["0287ca340e124b6b0fe08442a6c105e15fe015a2"];
+    assert_eq!(b.summary, "two");
+    assert!(!b.boundary);
+  }
+}
@@ -5,7 +5,8 @@ use crate::git::queries::commit_calcs::{
 };
 use crate::git::queries::commit_filters::{apply_commit_filters, CommitFilter};
 use crate::git::queries::commits_parsers::{
-  PRETTY_FORMATTED, P_COMMITS, P_COMMIT_ROW, P_ID_LIST,
+  PRETTY_FORMATTED, PRETTY_FORMATTED_SIGNED, P_COMMITS, P_COMMIT_ROW, P_ID_LIST,
+  P_SIGNED_COMMITS,
 };
 use crate::git::queries::refs::head_info::{calc_head_info, HeadInfo};
 use crate::git::queries::refs::{finish_properties_on_refs, get_ref_info_from_commits};
@@ -73,6 +74,9 @@ pub struct ReqCommitsOptions2 {
   pub filters: Vec<CommitFilter>,
   pub fast: bool, // Fast means to use the cache only, don't run git command.
   pub skip_stashes: bool,
+  // Slow. Git has to verify each signed commit.
+  #[serde(default)]
+  pub verify_signatures: bool,
 }
 
 pub fn load_commits_and_refs(
@@ -84,10 +88,16 @@ pub fn load_commits_and_refs(
     filters,
     fast,
     skip_stashes,
+    verify_signatures,
   } = options;
 
-  let (commits, refs) =
-    load_commits_unfiltered(repo_path, *num_commits, *fast, *skip_stashes)?;
+  let (commits, refs) = load_commits_unfiltered(
+    repo_path,
+    *num_commits,
+    *fast,
+    *skip_stashes,
+    *verify_signatures,
+  )?;
 
   Ok((
     apply_commit_filters(repo_path, commits, &refs, filters),
@@ -111,6 +121,7 @@ pub fn convert_commit(commit_info: CommitInfo) -> Commit {
     message: commit_info.message,
     stash_id: commit_info.stash_id,
     refs: commit_info.refs.into_iter().map(|r| r.id).collect(),
+    signature: commit_info.signature,
     filtered: commit_info.filtered,
     num_skipped: commit_info.num_skipped,
   }
@@ -121,6 +132,7 @@ fn load_commits_unfiltered(
   num_commits: u32,
   cache_only: bool,
   skip_stashes: bool,
+  verify_signatures: bool,
 ) -> R<(Vec<Commit>, Vec<RefInfo>)> {
   if cache_only {
     if let Some(commits) = STORE.get_commits_and_refs(repo_path) {
@@ -129,14 +141,14 @@ fn load_commits_unfiltered(
   }
 
   let mut commits = if skip_stashes {
-    load_commits(repo_path, num_commits)?
+    load_commits(repo_path, num_commits, verify_signatures)?
   } else {
     let p1 = repo_path.clone();
     let p2 = repo_path.clone();
     let num = num_commits;
 
     let stashes_thread = thread::spawn(move || load_stashes(&p1));
-    let commits_thread = thread::spawn(move || load_commits(&p2, num));
+    let commits_thread = thread::spawn(move || load_commits(&p2, num, verify_signatures));
 
     let stashes = stashes_thread.join()?;
     let mut commits = commits_thread.join()??;
@@ -168,7 +180,11 @@ fn load_commits_unfiltered(
   Ok((commits, refs))
 }
 
-pub fn load_commits(repo_path: &PathString, num: u32) -> R<Vec<CommitInfo>> {
+pub fn load_commits(
+  repo_path: &PathString,
+  num: u32,
+  verify_signatures: bool,
+) -> R<Vec<CommitInfo>> {
   let out = run_git_err(RunGitOptions {
     args: [
       "log",
@@ -176,7 +192,11 @@ pub fn load_commits(repo_path: &PathString, num: u32) -> R<Vec<CommitInfo>> {
       "--tags",
       "--remotes",
       "--decorate=full",
-      PRETTY_FORMATTED,
+      if verify_signatures {
+        PRETTY_FORMATTED_SIGNED
+      } else {
+        PRETTY_FORMATTED
+      },
       format!("-n{}", num).as_str(),
       "--date=raw",
     ],
@@ -185,7 +205,11 @@ pub fn load_commits(repo_path: &PathString, num: u32) -> R<Vec<CommitInfo>> {
   .stdout;
 
   time_result!(format!("parse commits. Length {}", out.len()), {
-    parse_all_err(P_COMMITS, &out)
+    if verify_signatures {
+      parse_all_err(P_SIGNED_COMMITS, &out)
+    } else {
+      parse_all_err(P_COMMITS, &out)
+    }
   })
 }
 
@@ -1,16 +1,26 @@
-use crate::git::git_types::{CommitInfo, DateResult};
+use crate::git::git_types::{CommitInfo, CommitSignature, DateResult, SignatureStatus};
 use crate::git::queries::refs::{make_ref_info, RefInfoPart};
 use crate::git::queries::{RefInfo, P_OPTIONAL_REFS};
 use crate::parser::standard_parsers::{
   ANY_WORD, SIGNED_INT, UNSIGNED_INT, UNTIL_LINE_END, WS,
 };
 use crate::parser::Parser;
-use crate::{and, character, many, map, or, rep_parser_sep, take_char_while, until_str};
+use crate::{
+  and, character, conditional_char, many, map, or, rep_parser_sep, take_char_while,
+  until_str,
+};
 
 const END: &str = "4a41380f-a4e8-4251-9ca2-bf55186ed32a";
 pub const PRETTY_FORMATTED: &str =
   "--pretty=format:%an; %ae; %ad; %H; %P; %B4a41380f-a4e8-4251-9ca2-bf55186ed32a; %d";
 
+const SIGNATURE_END: &str = "8f0bd4e6-3c8e-4c1f-9b62-58f3f1a7d2c9";
+// Same as PRETTY_FORMATTED, but prefixed with signature info (status, key, signer).
+// This is slow as git has to verify every commit. SSH signatures can only be verified
+// when gpg.ssh.allowedSignersFile is configured, otherwise they come back as UnknownKey.
+pub const PRETTY_FORMATTED_SIGNED: &str =
+  "--pretty=format:%G?; %GK; %GS8f0bd4e6-3c8e-4c1f-9b62-58f3f1a7d2c9; %an; %ae; %ad; %H; %P; %B4a41380f-a4e8-4251-9ca2-bf55186ed32a; %d";
+
 pub const SEP_CHAR: char = ';';
 
 pub const P_GROUP: Parser<String> = take_char_while!(|c: char| { c != SEP_CHAR });
@@ -23,7 +33,7 @@ const P_SEP: Parser<char> = map!(and!(WS, character!(SEP_CHAR), WS), |_res: (
 
 const P_EMAIL: Parser<String> = or!(P_GROUP, WS);
 
-const P_DATE: Parser<DateResult> = map!(and!(UNSIGNED_INT, WS, SIGNED_INT), |res: (
+pub const P_DATE: Parser<DateResult> = map!(and!(UNSIGNED_INT, WS, SIGNED_INT), |res: (
   String,
   String,
   String
@@ -93,6 +103,7 @@ pub const P_COMMIT_ROW: Parser<CommitInfo> = map!(
       message: result.10,
       stash_id: String::new(),
       refs,
+      signature: None,
       filtered: false,
       num_skipped: 0,
     }
@@ -101,4 +112,49 @@ pub const P_COMMIT_ROW: Parser<CommitInfo> = map!(
 
 pub const P_COMMITS: Parser<Vec<CommitInfo>> = many!(P_COMMIT_ROW);
 
+const P_SIGNATURE: Parser<CommitSignature> = map!(
+  and!(
+    conditional_char!(|c: char| c != SEP_CHAR),
+    P_SEP,
+    or!(P_GROUP, WS), // key id
+    P_SEP,
+    until_str!(SIGNATURE_END), // signer
+    P_SEP
+  ),
+  |result: (char, char, String, char, String, char)| {
+    CommitSignature {
+      status: signature_status_from_char(result.0),
+      signer: result.4.trim().to_string(),
+      key_id: result.2,
+    }
+  }
+);
+
+pub const P_SIGNED_COMMIT_ROW: Parser<CommitInfo> = map!(
+  and!(P_SIGNATURE, P_COMMIT_ROW),
+  |result: (CommitSignature, CommitInfo)| {
+    let (signature, mut commit) = result;
+
+    if signature.status != SignatureStatus::None {
+      commit.signature = Some(signature);
+    }
+
+    commit
+  }
+);
+
+pub const P_SIGNED_COMMITS: Parser<Vec<CommitInfo>> = many!(P_SIGNED_COMMIT_ROW);
+
+fn signature_status_from_char(c: char) -> SignatureStatus {
+  match c {
+    'G' => SignatureStatus::Good,
+    'U' => SignatureStatus::Untrusted,
+    'B' => SignatureStatus::Bad,
+    'E' => SignatureStatus::UnknownKey,
+    'X' | 'Y' => SignatureStatus::Expired,
+    'R' => SignatureStatus::Revoked,
+    _ => SignatureStatus::None,
+  }
+}
+
 pub const P_ID_LIST: Parser<Vec<String>> = rep_parser_sep!(ANY_WORD, UNTIL_LINE_END);
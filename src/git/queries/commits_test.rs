@@ -2,8 +2,11 @@
 mod tests {
   use std::env::current_dir;
 
-  use crate::git::queries::commits::load_commits;
-  use crate::git::queries::commits_parsers::{P_COMMIT_ROW, P_GROUP, P_ID_LIST};
+  use crate::git::git_types::SignatureStatus;
+  use crate::git::queries::commits::{load_commits, ReqCommitsOptions2};
+  use crate::git::queries::commits_parsers::{
+    P_COMMIT_ROW, P_GROUP, P_ID_LIST, P_SIGNED_COMMITS, P_SIGNED_COMMIT_ROW,
+  };
   use crate::parser::{parse_all, parse_part};
 
   #[test]
@@ -29,12 +32,50 @@ mod tests {
     assert_eq!(c.author, "Firstname Lastname");
   }
 
+  #[test]
+  fn test_p_signed_commit_row() {
+    let res = parse_all(
+      P_SIGNED_COMMIT_ROW,
+      "G; 4AEE18F83AFDEB23; Firstname Lastname <sugto555@gmail.com>\
+      8f0bd4e6-3c8e-4c1f-9b62-58f3f1a7d2c9; \
+      Firstname Lastname; sugto555@gmail.com; 1648863350 +1300; \
+      dd5733ad96082f0f33164bd1e2d72f7540bf7d9f; 2e8966986f620f491c34e6243a546d85dd2322e0; \
+      Signed commit. 4a41380f-a4e8-4251-9ca2-bf55186ed32a; ",
+    );
+
+    assert!(res.is_some());
+    let signature = res.unwrap().signature.unwrap();
+    assert_eq!(signature.status, SignatureStatus::Good);
+    assert_eq!(signature.key_id, "4AEE18F83AFDEB23");
+    assert_eq!(signature.signer, "Firstname Lastname <sugto555@gmail.com>");
+  }
+
+  #[test]
+  fn test_p_signed_commits_unsigned() {
+    let res = parse_all(
+      P_SIGNED_COMMITS,
+      "N; ; 8f0bd4e6-3c8e-4c1f-9b62-58f3f1a7d2c9; \
+      Firstname Lastname; sugto555@gmail.com; 1648863350 +1300; \
+      dd5733ad96082f0f33164bd1e2d72f7540bf7d9f; 2e8966986f620f491c34e6243a546d85dd2322e0; \
+      Not signed. 4a41380f-a4e8-4251-9ca2-bf55186ed32a; (HEAD -> refs/heads/master)
+N; ; 8f0bd4e6-3c8e-4c1f-9b62-58f3f1a7d2c9; \
+      Firstname Lastname; sugto555@gmail.com; 1648863340 +1300; \
+      2e8966986f620f491c34e6243a546d85dd2322e0; ; \
+      First. 4a41380f-a4e8-4251-9ca2-bf55186ed32a; ",
+    );
+
+    assert!(res.is_some());
+    let commits = res.unwrap();
+    assert_eq!(commits.len(), 2);
+    assert!(commits[0].signature.is_none());
+  }
+
   #[test]
   fn test_load_commits() {
     let cwd = current_dir().unwrap();
     let repo_path = cwd.into_os_string().into_string().unwrap();
 
-    let result = load_commits(&repo_path, 5);
+    let result = load_commits(&repo_path, 5, false);
 
     assert!(result.is_ok());
   }
@@ -65,4 +106,14 @@ mod tests {
     assert!(out.is_some());
     assert_eq!(out.unwrap().len(), 4);
   }
+
+  #[test]
+  fn test_commits_options_without_verify_signatures() {
+    let options: ReqCommitsOptions2 = serde_json::from_str(
+      r#"{"repoPath": ".", "numCommits": 10, "filters": [], "fast": false, "skipStashes": false}"#,
+    )
+    .unwrap();
+
+    assert!(!options.verify_signatures);
+  }
 }
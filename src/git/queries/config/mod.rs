@@ -1,7 +1,7 @@
 use crate::git::queries::config::config_file_parser::make_config_log;
 use crate::git::queries::config::config_output_parser::P_SUBMODULE_NAME;
 use crate::git::run_git::{run_git_err, RunGitOptions};
-use crate::git::store::{CONFIG, STORE};
+use crate::git::store::{CONFIG, CONFIG_FALLBACKS, STORE};
 use crate::parser::{parse_all_err, run_parser, ParseOptions};
 use crate::server::git_request::ReqOptions;
 use crate::server::request_util::R;
@@ -56,6 +56,46 @@ impl GitConfig {
 
     format!("refs/remotes/{}/{}", remote, local_branch)
   }
+
+  // Keys read from the config file keep their original case, e.g. "user.signingKey".
+  pub fn get_value(&self, key: &str) -> Option<String> {
+    let key = key.to_lowercase();
+
+    self
+      .entries
+      .iter()
+      .find(|(k, _)| k.to_lowercase() == key)
+      .map(|(_, v)| v.clone())
+  }
+}
+
+// Our stored config only comes from the repo's config file. Settings like
+// user.signingKey usually live in the global config, so fall back to asking git. The
+// answer is kept until the config is reloaded, as this is called for every diff.
+pub fn read_config_value(repo_path: &str, key: &str) -> Option<String> {
+  if let Some(config) = CONFIG.get_by_key(&repo_path.to_string()) {
+    if let Some(value) = config.get_value(key) {
+      return Some(value);
+    }
+  }
+
+  let cache_key = (repo_path.to_string(), key.to_lowercase());
+
+  if let Some(value) = CONFIG_FALLBACKS.get_by_key(&cache_key) {
+    return value;
+  }
+
+  let out = run_git_err(RunGitOptions {
+    repo_path,
+    args: ["config", "--get", key],
+  })
+  .ok()?;
+
+  let value = Some(out.stdout.trim().to_string()).filter(|v| !v.is_empty());
+
+  CONFIG_FALLBACKS.insert(cache_key, value.clone());
+
+  value
 }
 
 // Use this version on focus of GitFiend only. Get it from the store otherwise.
@@ -121,5 +161,9 @@ pub fn load_full_config(options: &ReqOptions) -> R<GitConfig> {
 
   CONFIG.insert(repo_path.clone(), config.clone());
 
+  if let Ok(mut fallbacks) = CONFIG_FALLBACKS.data.write() {
+    fallbacks.retain(|(path, _), _| path != repo_path);
+  }
+
   Ok(config)
 }
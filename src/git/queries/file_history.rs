@@ -0,0 +1,178 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::f;
+use crate::git::git_types::{Commit, Patch};
+use crate::git::queries::commits::convert_commit;
+use crate::git::queries::commits_parsers::{PRETTY_FORMATTED, P_COMMITS};
+use crate::git::queries::patches::patch_parsers::P_MANY_PATCHES_WITH_COMMIT_IDS;
+use crate::git::run_git::{run_git_err, RunGitOptions};
+use crate::git::store::STORE;
+use crate::parser::parse_all_err;
+use crate::server::request_util::R;
+
+#[derive(Debug, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct ReqFileHistoryOptions {
+  pub repo_path: String,
+  // Path of the file at start_commit_id.
+  pub file_path: String,
+  // Defaults to HEAD.
+  pub start_commit_id: Option<String>,
+  pub skip: u32,
+  pub num: u32,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct FileHistory {
+  pub entries: Vec<FileHistoryEntry>,
+  pub has_more: bool,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct FileHistoryEntry {
+  pub commit: Commit,
+  // Pass this to load_hunks. Only includes the followed file.
+  pub patch: Patch,
+  // The file's path in this commit. Differs from file_path if it has been renamed since.
+  pub path: String,
+}
+
+// Unlike CommitFilter::File this isn't limited to loaded commits, and follows renames.
+pub fn load_file_history(options: &ReqFileHistoryOptions) -> R<FileHistory> {
+  let ReqFileHistoryOptions {
+    repo_path,
+    file_path,
+    start_commit_id,
+    skip,
+    num,
+  } = options;
+
+  let out = run_git_err(RunGitOptions {
+    repo_path,
+    args: [
+      f!("log"),
+      f!("--follow"),
+      // Merges don't list patches. Leaving them out here keeps skip and num accurate.
+      f!("--no-merges"),
+      f!("--name-status"),
+      f!("--pretty=format:%H,"),
+      f!("-z"),
+      f!("--skip={}", skip),
+      // Load one extra to find out if there are more.
+      f!("-n{}", num + 1),
+      start_commit_id.clone().unwrap_or(f!("HEAD")),
+      f!("--"),
+      file_path.clone(),
+    ],
+  })?
+  .stdout;
+
+  let (commit_patches, has_more) = parse_file_history(&out, *num)?;
+
+  let ids: Vec<&String> = commit_patches.iter().map(|(id, _)| id).collect();
+  let commits = load_commits_by_id(repo_path, &ids)?;
+
+  let entries = commit_patches
+    .into_iter()
+    .filter_map(|(id, patch)| {
+      let commit = commits.get(&id)?.clone();
+
+      Some(FileHistoryEntry {
+        commit,
+        path: patch.new_file.clone(),
+        patch,
+      })
+    })
+    .collect();
+
+  Ok(FileHistory { entries, has_more })
+}
+
+// Expects num + 1 commits at most, the extra one only tells us there are more.
+fn parse_file_history(out: &str, num: u32) -> R<(Vec<(String, Patch)>, bool)> {
+  let mut commit_patches: Vec<(String, Patch)> =
+    parse_all_err(P_MANY_PATCHES_WITH_COMMIT_IDS, out)?
+      .into_iter()
+      .filter_map(|(id, patches)| Some((id, patches.into_iter().next()?)))
+      .collect();
+
+  let has_more = commit_patches.len() > num as usize;
+  commit_patches.truncate(num as usize);
+
+  Ok((commit_patches, has_more))
+}
+
+// Prefers commits we already have in the store. Others are loaded from git.
+pub fn load_commits_by_id(
+  repo_path: &str,
+  ids: &[&String],
+) -> R<HashMap<String, Commit>> {
+  let mut commits: HashMap<String, Commit> = HashMap::new();
+
+  if let Some((loaded, _)) = STORE.get_commits_and_refs(&repo_path.to_string()) {
+    for c in loaded {
+      if ids.contains(&&c.id) {
+        commits.insert(c.id.clone(), c);
+      }
+    }
+  }
+
+  let missing: Vec<String> = ids
+    .iter()
+    .filter(|id| !commits.contains_key(id.as_str()))
+    .map(|id| id.to_string())
+    .collect();
+
+  if !missing.is_empty() {
+    let mut args = vec![
+      f!("log"),
+      f!("--no-walk=unsorted"),
+      f!("--decorate=full"),
+      PRETTY_FORMATTED.to_string(),
+      f!("--date=raw"),
+    ];
+    args.extend(missing);
+
+    let out = run_git_err(RunGitOptions { repo_path, args })?.stdout;
+
+    for info in parse_all_err(P_COMMITS, &out)? {
+      commits.insert(info.id.clone(), convert_commit(info));
+    }
+  }
+
+  Ok(commits)
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::git::queries::file_history::parse_file_history;
+
+  const LOG: &str = "4f6c355e92c9d8c5954c380406c12179e8fcc524,\nM\0b\0\0\
+    aa75e8351f03d811f1ea33bb4ee1ddd3a58182d3,\nR100\0a\0b\0\0\
+    cda38a105f60d9f54cebf2eb0a4de7f4e389c724,\nA\0a\0";
+
+  #[test]
+  fn test_parse_file_history() {
+    let (entries, has_more) = parse_file_history(LOG, 2).unwrap();
+
+    assert!(has_more);
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].0, "4f6c355e92c9d8c5954c380406c12179e8fcc524");
+    assert_eq!(entries[0].1.commit_id, entries[0].0);
+    assert_eq!(entries[1].1.old_file, "a");
+    assert_eq!(entries[1].1.new_file, "b");
+
+    let (entries, has_more) = parse_file_history(LOG, 3).unwrap();
+
+    assert!(!has_more);
+    assert_eq!(entries[2].1.new_file, "a");
+  }
+}
@@ -0,0 +1,229 @@
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::f;
+use crate::git::git_types::{HunkLine, HunkLineStatus};
+use crate::git::queries::hunks::html_code::{first_line, DiffStats};
+use crate::git::queries::hunks::load_hunks::{flatten_hunks, load_hunks, ReqHunkOptions};
+use crate::git::queries::hunks::side_text::{load_commit_side_text, ContextSide};
+use crate::git::queries::syntax_colouring::token_classes::{CLASS_PREFIX, TEXT_CLASS};
+use crate::git::queries::syntax_colouring::{ColourLine, ThemeColour, COLOURING};
+use crate::git::queries::wip::file_attributes::load_file_attributes;
+use crate::git::queries::wip::wip_diff::{
+  load_wip_hunks_with_attributes, load_wip_texts, ReqWipHunksOptions, WipTexts,
+};
+use crate::server::request_util::R;
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct CodeToken {
+  pub text: String,
+  // Innermost scope, e.g. "keyword.control.rust". Empty if there isn't one.
+  pub scope: String,
+  // Same as the classes in ColourOutput::Classes, so get_syntax_stylesheet applies.
+  pub class: String,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct PatchTokens {
+  pub stats: DiffStats,
+  // One list per hunk line, in the same order as the hunk lines. Headers have no tokens.
+  // None when the diff is too large.
+  pub lines: Option<Vec<Vec<CodeToken>>>,
+}
+
+// The hunk options with "force", like the HTML requests.
+#[derive(Debug, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct ReqPatchTokensOptions {
+  #[serde(flatten)]
+  pub hunks: ReqHunkOptions,
+  // Tokenise even if over the size limits.
+  #[serde(default)]
+  pub force: bool,
+}
+
+pub fn get_patch_tokens(options: &ReqPatchTokensOptions) -> R<PatchTokens> {
+  let ReqPatchTokensOptions { hunks, force } = options;
+  let ReqHunkOptions {
+    repo_path,
+    commit,
+    patch,
+    ..
+  } = hunks;
+
+  let (hunks, hunk_lines) = load_hunks(hunks)?;
+
+  let stats = DiffStats::new(&hunks, hunk_lines.len());
+  if stats.too_large() && !*force {
+    return Ok(PatchTokens { stats, lines: None });
+  }
+
+  let old_text = load_commit_side_text(repo_path, commit, patch, ContextSide::Old).ok();
+  let new_text = load_commit_side_text(repo_path, commit, patch, ContextSide::New).ok();
+
+  // Classes don't depend on the theme.
+  let mut colouring = COLOURING.write()?;
+  let mut c = colouring.get_colour_line_for_file(
+    &ThemeColour::Light,
+    repo_path,
+    patch.get_file_path(),
+    first_line(&old_text, &new_text),
+  );
+  c.set_file_texts(old_text.as_deref(), new_text.as_deref());
+
+  Ok(PatchTokens {
+    stats,
+    lines: Some(tokenise_lines(&hunk_lines, &mut c)),
+  })
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct ReqWipPatchTokensOptions {
+  #[serde(flatten)]
+  pub wip: ReqWipHunksOptions,
+  // Tokenise even if over the size limits.
+  #[serde(default)]
+  pub force: bool,
+}
+
+pub fn get_wip_patch_tokens(options: &ReqWipPatchTokensOptions) -> R<PatchTokens> {
+  let ReqWipPatchTokensOptions {
+    wip: options,
+    force,
+  } = options;
+
+  let attributes = load_file_attributes(&options.repo_path, &options.patch.new_file);
+  let (hunks, _, _) = load_wip_hunks_with_attributes(options, &attributes)?;
+  let hunk_lines = flatten_hunks(hunks.clone());
+
+  let stats = DiffStats::new(&hunks, hunk_lines.len());
+  if stats.too_large() && !*force {
+    return Ok(PatchTokens { stats, lines: None });
+  }
+
+  let (old_text, new_text) = match load_wip_texts(options, &attributes).ok() {
+    Some(WipTexts { old, new, .. }) => (Some(old), Some(new)),
+    None => (None, None),
+  };
+
+  let mut colouring = COLOURING.write()?;
+  let mut c = colouring.get_colour_line_for_file(
+    &ThemeColour::Light,
+    &options.repo_path,
+    options.patch.get_file_path(),
+    first_line(&old_text, &new_text),
+  );
+  c.set_file_texts(old_text.as_deref(), new_text.as_deref());
+
+  Ok(PatchTokens {
+    stats,
+    lines: Some(tokenise_lines(&hunk_lines, &mut c)),
+  })
+}
+
+fn tokenise_lines(
+  hunk_lines: &[HunkLine],
+  colour: &mut ColourLine,
+) -> Vec<Vec<CodeToken>> {
+  use HunkLineStatus::*;
+
+  let mut lines = Vec::with_capacity(hunk_lines.len());
+
+  for line in hunk_lines {
+    match line.status {
+      HeaderStart => {
+        colour.end_fragment();
+        lines.push(Vec::new());
+      }
+      HeaderEnd => {
+        colour.start_fragment();
+        lines.push(Vec::new());
+      }
+      Skip => lines.push(Vec::new()),
+      Added | Removed | Unchanged => lines.push(tokenise_line(line, colour)),
+    }
+  }
+
+  lines
+}
+
+fn tokenise_line(line: &HunkLine, colour: &mut ColourLine) -> Vec<CodeToken> {
+  let line_text = f!("{}\n", line.text);
+
+  let Ok(parts) = colour.colour_hunk_line(line, &line_text) else {
+    return vec![CodeToken {
+      text: line.text.clone(),
+      scope: String::new(),
+      class: f!("{}{}", CLASS_PREFIX, TEXT_CLASS),
+    }];
+  };
+
+  parts
+    .iter()
+    .filter_map(|token| {
+      let text = token.text.trim_end_matches('\n');
+      if text.is_empty() {
+        return None;
+      }
+
+      Some(CodeToken {
+        text: text.to_string(),
+        scope: token
+          .scopes
+          .last()
+          .map(|s| s.build_string())
+          .unwrap_or_default(),
+        class: colour.token_class(token),
+      })
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::git::queries::hunks::code_tokens::{
+    get_wip_patch_tokens, ReqWipPatchTokensOptions,
+  };
+  use crate::git::test_repo::TestRepo;
+
+  #[test]
+  fn test_wip_patch_tokens_force() {
+    let repo = TestRepo::new("wip_patch_tokens_force");
+    repo.write("a.txt", "a\n");
+    let head = repo.commit("first");
+    let lines: String = (0..20_001).map(|i| format!("{}\n", i)).collect();
+    repo.write("a.txt", format!("a\n{}", lines));
+
+    // Same as the old request without "force".
+    let json = serde_json::json!({
+      "repoPath": repo.path_str(),
+      "patch": {
+        "oldFile": "a.txt",
+        "newFile": "a.txt",
+        "patchType": "M",
+        "stagedType": " ",
+        "unStagedType": "M",
+        "conflicted": false,
+        "id": "a.txt",
+        "isImage": false,
+      },
+      "headCommit": head,
+    });
+    let mut options: ReqWipPatchTokensOptions = serde_json::from_value(json).unwrap();
+
+    let tokens = get_wip_patch_tokens(&options).unwrap();
+    assert!(tokens.stats.too_large());
+    assert!(tokens.lines.is_none());
+
+    options.force = true;
+    let tokens = get_wip_patch_tokens(&options).unwrap();
+    assert_eq!(tokens.lines.unwrap().len(), tokens.stats.total_lines);
+  }
+}
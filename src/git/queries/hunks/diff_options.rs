@@ -0,0 +1,139 @@
+use serde::{Deserialize, Serialize};
+use similar::Algorithm;
+use ts_rs::TS;
+
+use crate::f;
+
+#[derive(Debug, Clone, PartialEq, Eq, Default, Deserialize, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct DiffOptions {
+  pub whitespace: WhitespaceMode,
+  pub ignore_blank_lines: bool,
+  // Git's default is 3.
+  pub context_lines: Option<u32>,
+  pub algorithm: DiffAlgorithm,
+  // Tag moved blocks of code, like --color-moved. Done by us, not git.
+  pub detect_moved: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize, TS)]
+#[ts(export)]
+pub enum WhitespaceMode {
+  #[default]
+  Show,
+  // -w
+  IgnoreAll,
+  // -b
+  IgnoreChange,
+  // --ignore-space-at-eol
+  IgnoreEol,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize, TS)]
+#[ts(export)]
+pub enum DiffAlgorithm {
+  #[default]
+  Myers,
+  Patience,
+  Histogram,
+  Minimal,
+}
+
+impl DiffOptions {
+  pub fn context(&self) -> u32 {
+    self.context_lines.unwrap_or(3)
+  }
+
+  pub fn git_args(&self) -> Vec<String> {
+    let mut args = Vec::new();
+
+    match self.whitespace {
+      WhitespaceMode::Show => {}
+      WhitespaceMode::IgnoreAll => args.push(f!("--ignore-all-space")),
+      WhitespaceMode::IgnoreChange => args.push(f!("--ignore-space-change")),
+      WhitespaceMode::IgnoreEol => args.push(f!("--ignore-space-at-eol")),
+    }
+
+    if self.ignore_blank_lines {
+      args.push(f!("--ignore-blank-lines"));
+    }
+
+    if let Some(n) = self.context_lines {
+      args.push(f!("--unified={}", n));
+    }
+
+    let algorithm = match self.algorithm {
+      DiffAlgorithm::Myers => "myers",
+      DiffAlgorithm::Patience => "patience",
+      DiffAlgorithm::Histogram => "histogram",
+      DiffAlgorithm::Minimal => "minimal",
+    };
+    args.push(f!("--diff-algorithm={}", algorithm));
+
+    args
+  }
+
+  /*
+  For WIP diffs done with "similar". It doesn't have histogram, which is a refinement of
+  patience so is the closest match. Git's myers takes shortcuts on large inputs, while
+  similar's is always minimal.
+   */
+  pub fn similar_algorithm(&self) -> Algorithm {
+    match self.algorithm {
+      DiffAlgorithm::Myers | DiffAlgorithm::Minimal => Algorithm::Myers,
+      DiffAlgorithm::Patience | DiffAlgorithm::Histogram => Algorithm::Patience,
+    }
+  }
+
+  // Lines are compared by this key, so lines that only differ by ignored whitespace are
+  // considered equal. Expects the line ending to already be removed.
+  pub fn compare_key(&self, line: &str) -> String {
+    match self.whitespace {
+      WhitespaceMode::Show => line.to_string(),
+      WhitespaceMode::IgnoreAll => line.chars().filter(|c| !c.is_whitespace()).collect(),
+      WhitespaceMode::IgnoreChange => collapse_whitespace(line.trim_end()),
+      WhitespaceMode::IgnoreEol => line.trim_end().to_string(),
+    }
+  }
+}
+
+// Runs of whitespace become a single space.
+fn collapse_whitespace(line: &str) -> String {
+  let mut result = String::new();
+  let mut in_whitespace = false;
+
+  for c in line.chars() {
+    if c.is_whitespace() {
+      if !in_whitespace {
+        result.push(' ');
+      }
+      in_whitespace = true;
+    } else {
+      result.push(c);
+      in_whitespace = false;
+    }
+  }
+
+  result
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::git::queries::hunks::diff_options::{DiffOptions, WhitespaceMode};
+
+  #[test]
+  fn test_compare_key() {
+    let mut options = DiffOptions::default();
+    assert_eq!(options.compare_key(" a  b "), " a  b ");
+
+    options.whitespace = WhitespaceMode::IgnoreAll;
+    assert_eq!(options.compare_key(" a  b "), "ab");
+
+    options.whitespace = WhitespaceMode::IgnoreChange;
+    assert_eq!(options.compare_key("\t a  b "), " a b");
+
+    options.whitespace = WhitespaceMode::IgnoreEol;
+    assert_eq!(options.compare_key(" a  b "), " a  b");
+  }
+}
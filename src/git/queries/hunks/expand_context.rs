@@ -0,0 +1,444 @@
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::git::git_types::{
+  Commit, Hunk, HunkLine, HunkLineStatus, HunkRange, Patch, WipPatch,
+};
+use crate::git::queries::hunks::diff_options::DiffOptions;
+use crate::git::queries::hunks::line_warnings::{add_line_warnings, WhitespaceRules};
+use crate::git::queries::hunks::load_hunks::{flatten_hunks, load_hunks, ReqHunkOptions};
+use crate::git::queries::hunks::side_text::{load_commit_side_text, ContextSide};
+use crate::git::queries::wip::create_hunks::convert_lines_to_hunks;
+use crate::git::queries::wip::file_attributes::{load_file_attributes, FileAttributes};
+use crate::git::queries::wip::wip_diff::{
+  load_wip_hunk_lines_with_attributes, load_wip_hunks_with_attributes, load_wip_texts,
+  ReqWipHunksOptions, WipDiffKind,
+};
+use crate::server::request_util::{ES, R};
+
+// Large enough for any file. Git is fine with this.
+const FULL_FILE_CONTEXT: u32 = i32::MAX as u32;
+
+#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub enum ContextSource {
+  Commit {
+    commit: Box<Commit>,
+    patch: Patch,
+  },
+  Wip {
+    patch: WipPatch,
+    #[serde(rename = "headCommit")]
+    head_commit: Option<String>,
+    // Which of the WIP diffs is shown.
+    #[serde(default)]
+    kind: WipDiffKind,
+  },
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub enum ContextRange {
+  // 1 based, inclusive, line numbers on one side of the diff. Should be between hunks.
+  Lines {
+    side: ContextSide,
+    start: u32,
+    end: u32,
+  },
+  // All hunks joined into one, with every line of the file as context.
+  WholeFile,
+}
+
+#[derive(Debug, Deserialize, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct ReqExpandContextOptions {
+  pub repo_path: String,
+  pub source: ContextSource,
+  pub range: ContextRange,
+  #[serde(default)]
+  pub diff_options: DiffOptions,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct ExpandedContext {
+  // Only set for WholeFile.
+  pub hunks: Vec<Hunk>,
+  // Just the requested Unchanged lines for Lines.
+  pub hunk_lines: Vec<HunkLine>,
+}
+
+pub fn expand_hunk_context(options: &ReqExpandContextOptions) -> R<ExpandedContext> {
+  let attributes = load_source_attributes(options);
+
+  match &options.range {
+    ContextRange::Lines { side, start, end } => Ok(ExpandedContext {
+      hunks: Vec::new(),
+      hunk_lines: load_context_lines(options, &attributes, *side, *start, *end)?,
+    }),
+    ContextRange::WholeFile => {
+      let hunks = load_whole_file_hunks(options, &attributes)?;
+
+      Ok(ExpandedContext {
+        hunk_lines: flatten_hunks(hunks.clone()),
+        hunks,
+      })
+    }
+  }
+}
+
+// Only WIP files are read with their attributes. Git reads them itself for commits.
+fn load_source_attributes(options: &ReqExpandContextOptions) -> FileAttributes {
+  match &options.source {
+    ContextSource::Wip { patch, .. } => {
+      load_file_attributes(&options.repo_path, &patch.new_file)
+    }
+    ContextSource::Commit { .. } => FileAttributes::default(),
+  }
+}
+
+fn load_whole_file_hunks(
+  options: &ReqExpandContextOptions,
+  attributes: &FileAttributes,
+) -> R<Vec<Hunk>> {
+  let ReqExpandContextOptions {
+    repo_path,
+    source,
+    diff_options,
+    ..
+  } = options;
+
+  match source {
+    ContextSource::Commit { commit, patch } => {
+      let (hunks, _) = load_hunks(&ReqHunkOptions {
+        repo_path: repo_path.clone(),
+        commit: *commit.clone(),
+        patch: patch.clone(),
+        diff_options: DiffOptions {
+          context_lines: Some(FULL_FILE_CONTEXT),
+          ..diff_options.clone()
+        },
+      })?;
+
+      Ok(hunks)
+    }
+    ContextSource::Wip {
+      patch,
+      head_commit,
+      kind,
+    } => {
+      let (lines, _) = load_wip_hunk_lines_with_attributes(
+        &ReqWipHunksOptions {
+          repo_path: repo_path.clone(),
+          patch: patch.clone(),
+          head_commit: head_commit.clone(),
+          diff_options: diff_options.clone(),
+          kind: *kind,
+        },
+        attributes,
+      )?;
+
+      let diff_options = DiffOptions {
+        context_lines: Some(lines.len() as u32),
+        ..diff_options.clone()
+      };
+
+      Ok(convert_lines_to_hunks(lines, &diff_options).0)
+    }
+  }
+}
+
+/*
+Lines between hunks are the same on both sides, just offset by the lines added and
+removed in the hunks before them. The range is cut short if it runs into the next hunk.
+ */
+fn load_context_lines(
+  options: &ReqExpandContextOptions,
+  attributes: &FileAttributes,
+  side: ContextSide,
+  start: u32,
+  end: u32,
+) -> R<Vec<HunkLine>> {
+  if start == 0 || end < start {
+    return Err(ES::from("Invalid line range"));
+  }
+
+  let hunks = load_source_hunks(options, attributes)?;
+  let text = load_side_text(options, attributes, side)?;
+
+  let mut offset = 0;
+  let mut end = end as i32;
+
+  for hunk in &hunks {
+    let (this_side, other_side) = match side {
+      ContextSide::Old => (&hunk.old_line_range, &hunk.new_line_range),
+      ContextSide::New => (&hunk.new_line_range, &hunk.old_line_range),
+    };
+
+    if next_line(this_side) <= start as i32 {
+      offset = next_line(other_side) - next_line(this_side);
+    } else {
+      end = end.min(first_line(this_side) - 1);
+      break;
+    }
+  }
+
+  let mut lines = Vec::<HunkLine>::new();
+
+  for (i, line) in text.split_inclusive('\n').enumerate() {
+    let num = i as i32 + 1;
+
+    if num < start as i32 {
+      continue;
+    }
+    if num > end {
+      break;
+    }
+
+    let (old_num, new_num) = match side {
+      ContextSide::Old => (num, num + offset),
+      ContextSide::New => (num - offset, num),
+    };
+
+    let text = line.trim_end_matches('\n');
+    let (text, line_ending) = match text.strip_suffix('\r') {
+      Some(text) => (text, "\r\n"),
+      None => (text, &line[text.len()..]),
+    };
+
+    lines.push(HunkLine {
+      status: HunkLineStatus::Unchanged,
+      old_num: Some(old_num),
+      new_num: Some(new_num),
+      hunk_index: -1,
+      text: text.to_string(),
+      index: lines.len() as u32,
+      line_ending: line_ending.to_string(),
+      changed_ranges: Vec::new(),
+      moved: None,
+      warnings: Vec::new(),
+    });
+  }
+
+  add_line_warnings(&mut lines, &WhitespaceRules::default());
+
+  Ok(lines)
+}
+
+// The first line number after the range. Empty ranges start at the line before.
+fn next_line(range: &HunkRange) -> i32 {
+  if range.length == 0 {
+    range.start + 1
+  } else {
+    range.start + range.length
+  }
+}
+
+fn first_line(range: &HunkRange) -> i32 {
+  if range.length == 0 {
+    range.start + 1
+  } else {
+    range.start
+  }
+}
+
+fn load_source_hunks(
+  options: &ReqExpandContextOptions,
+  attributes: &FileAttributes,
+) -> R<Vec<Hunk>> {
+  let ReqExpandContextOptions {
+    repo_path,
+    source,
+    diff_options,
+    ..
+  } = options;
+
+  match source {
+    ContextSource::Commit { commit, patch } => Ok(
+      load_hunks(&ReqHunkOptions {
+        repo_path: repo_path.clone(),
+        commit: *commit.clone(),
+        patch: patch.clone(),
+        diff_options: diff_options.clone(),
+      })?
+      .0,
+    ),
+    ContextSource::Wip {
+      patch,
+      head_commit,
+      kind,
+    } => Ok(
+      load_wip_hunks_with_attributes(
+        &ReqWipHunksOptions {
+          repo_path: repo_path.clone(),
+          patch: patch.clone(),
+          head_commit: head_commit.clone(),
+          diff_options: diff_options.clone(),
+          kind: *kind,
+        },
+        attributes,
+      )?
+      .0,
+    ),
+  }
+}
+
+fn load_side_text(
+  options: &ReqExpandContextOptions,
+  attributes: &FileAttributes,
+  side: ContextSide,
+) -> R<String> {
+  let repo_path = &options.repo_path;
+
+  match &options.source {
+    ContextSource::Commit { commit, patch } => {
+      load_commit_side_text(repo_path, commit, patch, side)
+    }
+    // The same texts the diff was made from, e.g. the index is the old side of Unstaged.
+    ContextSource::Wip {
+      patch,
+      head_commit,
+      kind,
+    } => {
+      let texts = load_wip_texts(
+        &ReqWipHunksOptions {
+          repo_path: repo_path.clone(),
+          patch: patch.clone(),
+          head_commit: head_commit.clone(),
+          diff_options: options.diff_options.clone(),
+          kind: *kind,
+        },
+        attributes,
+      )?;
+
+      Ok(match side {
+        ContextSide::Old => texts.old,
+        ContextSide::New => texts.new,
+      })
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::git::git_types::{HunkRange, WipPatch, WipPatchType};
+  use crate::git::queries::hunks::diff_options::DiffOptions;
+  use crate::git::queries::hunks::expand_context::{
+    expand_hunk_context, first_line, next_line, ContextRange, ContextSide, ContextSource,
+    ReqExpandContextOptions,
+  };
+  use crate::git::queries::wip::wip_diff::WipDiffKind;
+  use crate::git::test_repo::TestRepo;
+
+  #[test]
+  fn test_hunk_range_lines() {
+    let added = HunkRange {
+      start: 5,
+      length: 0,
+    };
+    let range = HunkRange {
+      start: 6,
+      length: 2,
+    };
+
+    assert_eq!(next_line(&added), 6);
+    assert_eq!(first_line(&added), 6);
+    assert_eq!(next_line(&range), 8);
+    assert_eq!(first_line(&range), 6);
+  }
+
+  fn context_texts(
+    repo: &TestRepo,
+    head: &str,
+    kind: WipDiffKind,
+    side: ContextSide,
+    start: u32,
+    end: u32,
+  ) -> Vec<(String, Option<i32>, Option<i32>)> {
+    let options = ReqExpandContextOptions {
+      repo_path: repo.path_str(),
+      source: ContextSource::Wip {
+        patch: WipPatch {
+          old_file: "a.txt".to_string(),
+          new_file: "a.txt".to_string(),
+          patch_type: WipPatchType::M,
+          staged_type: WipPatchType::M,
+          un_staged_type: WipPatchType::M,
+          conflicted: false,
+          id: "a.txt".to_string(),
+          is_image: false,
+          is_binary: false,
+        },
+        head_commit: Some(head.to_string()),
+        kind,
+      },
+      range: ContextRange::Lines { side, start, end },
+      diff_options: DiffOptions::default(),
+    };
+
+    expand_hunk_context(&options)
+      .unwrap()
+      .hunk_lines
+      .into_iter()
+      .map(|l| (l.text, l.old_num, l.new_num))
+      .collect()
+  }
+
+  #[test]
+  fn test_expand_wip_context_by_kind() {
+    let repo = TestRepo::new("expand_wip_context_by_kind");
+    let lines: Vec<String> = (1..=20).map(|n| n.to_string()).collect();
+    repo.write("a.txt", lines.join("\n") + "\n");
+    let head = repo.commit("init");
+
+    // Line 2 is changed in the index, line 18 only in the working copy.
+    let mut staged = lines.clone();
+    staged[1] = "two".to_string();
+    repo.write("a.txt", staged.join("\n") + "\n");
+    repo.git(&["add", "a.txt"]);
+
+    let mut working = staged.clone();
+    working.insert(17, "extra".to_string());
+    repo.write("a.txt", working.join("\n") + "\n");
+
+    // The index is the new side of Staged, so the unstaged line isn't there.
+    let staged_lines =
+      context_texts(&repo, &head, WipDiffKind::Staged, ContextSide::New, 17, 19);
+    assert_eq!(
+      staged_lines,
+      vec![
+        ("17".to_string(), Some(17), Some(17)),
+        ("18".to_string(), Some(18), Some(18)),
+        ("19".to_string(), Some(19), Some(19)),
+      ]
+    );
+
+    // The index is the old side of Unstaged, so it has the staged change.
+    let unstaged_lines =
+      context_texts(&repo, &head, WipDiffKind::Unstaged, ContextSide::Old, 1, 3);
+    assert_eq!(
+      unstaged_lines,
+      vec![
+        ("1".to_string(), Some(1), Some(1)),
+        ("two".to_string(), Some(2), Some(2)),
+        ("3".to_string(), Some(3), Some(3)),
+      ]
+    );
+
+    // Combined has both changes, so the range stops before the first hunk.
+    let combined_lines = context_texts(
+      &repo,
+      &head,
+      WipDiffKind::Combined,
+      ContextSide::Old,
+      10,
+      20,
+    );
+    assert_eq!(combined_lines.len(), 5);
+    assert_eq!(combined_lines[0], ("10".to_string(), Some(10), Some(10)));
+  }
+}
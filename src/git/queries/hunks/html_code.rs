@@ -1,16 +1,30 @@
 use crate::f;
-use crate::git::git_types::{Commit, Hunk, HunkLine, HunkLineStatus, Patch};
-use crate::git::queries::hunks::html_code_split::generate_lines_split;
+use crate::git::git_types::{
+  CharRange, Commit, Hunk, HunkLine, HunkLineStatus, LineWarningKind, Patch,
+};
+use crate::git::queries::hunks::diff_options::DiffOptions;
+use crate::git::queries::hunks::html_code_split::{
+  generate_lines_split, generate_lines_split_page,
+};
+use crate::git::queries::hunks::line_warnings::{is_bidi_control, is_zero_width};
 use crate::git::queries::hunks::load_hunks::{
-  load_hunks, load_hunks_split, ReqHunkOptions,
+  flatten_hunks, flatten_hunks_split, load_hunks, load_hunks_between,
+  load_hunks_between_split, load_hunks_split, ReqHunkOptions, ReqHunksBetweenOptions,
+};
+use crate::git::queries::hunks::side_text::{
+  load_commit_side_text, load_file_at_revision, merge_base, ContextSide,
 };
 use crate::git::queries::syntax_colouring::{
-  colour_to_style, ColourLine, ThemeColour, COLOURING,
+  colour_to_style, ColourLine, ColourOutput, ThemeColour, Token, COLOURING,
+};
+use crate::git::queries::wip::file_attributes::load_file_attributes;
+use crate::git::queries::wip::wip_diff::{
+  load_wip_hunks_with_attributes, load_wip_texts, ReqWipHunksOptions, WipTexts,
 };
 use crate::server::request_util::R;
 use serde::{Deserialize, Serialize};
 use std::fmt::Display;
-use syntect::highlighting::Style;
+use std::ops::Range;
 use ts_rs::TS;
 
 #[derive(Debug, Deserialize, Serialize, TS)]
@@ -22,6 +36,13 @@ pub struct ReqPatchCodeOptions {
   pub patch: Patch,
   pub theme: ThemeColour,
   pub split: bool,
+  #[serde(default)]
+  pub diff_options: DiffOptions,
+  #[serde(default)]
+  pub output: ColourOutput,
+  // Render even if over the size limits.
+  #[serde(default)]
+  pub force: bool,
 }
 
 pub fn get_patch_as_html(options: &ReqPatchCodeOptions) -> R<String> {
@@ -31,48 +52,459 @@ pub fn get_patch_as_html(options: &ReqPatchCodeOptions) -> R<String> {
     patch,
     theme,
     split,
+    diff_options,
+    output,
+    force,
+  } = options;
+
+  let hunk_options = ReqHunkOptions {
+    repo_path: repo_path.clone(),
+    commit: commit.clone(),
+    patch: patch.clone(),
+    diff_options: diff_options.clone(),
+  };
+
+  let (hunks, left, right) = if *split {
+    load_hunks_split(&hunk_options)?
+  } else {
+    let (hunks, hunk_lines) = load_hunks(&hunk_options)?;
+    (hunks, hunk_lines, Vec::new())
+  };
+
+  let stats = DiffStats::new(&hunks, left.len().max(right.len()));
+  if stats.too_large() && !*force {
+    return Ok(too_large_html(&stats));
+  }
+
+  let old_text = load_commit_side_text(repo_path, commit, patch, ContextSide::Old).ok();
+  let new_text = load_commit_side_text(repo_path, commit, patch, ContextSide::New).ok();
+
+  let mut colouring = COLOURING.write()?;
+  let mut c = colouring.get_colour_line_for_file(
+    theme,
+    repo_path,
+    patch.get_file_path(),
+    first_line(&old_text, &new_text),
+  );
+  c.set_file_texts(old_text.as_deref(), new_text.as_deref());
+  c.output = *output;
+
+  Ok(render_lines(&hunks, &left, &right, *split, &mut c))
+}
+
+// For picking the syntax when the file name isn't enough, e.g. scripts with a shebang.
+pub fn first_line<'a>(
+  old_text: &'a Option<String>,
+  new_text: &'a Option<String>,
+) -> Option<&'a str> {
+  new_text
+    .as_deref()
+    .filter(|t| !t.is_empty())
+    .or(old_text.as_deref())?
+    .lines()
+    .next()
+}
+
+// "right" is ignored if not split.
+fn render_lines(
+  hunks: &[Hunk],
+  left: &[HunkLine],
+  right: &[HunkLine],
+  split: bool,
+  colour: &mut ColourLine,
+) -> String {
+  if split {
+    generate_lines_split(left, right, hunks, colour)
+  } else {
+    generate_lines(left, hunks, colour)
+  }
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct ReqWipPatchCodeOptions {
+  pub wip: ReqWipHunksOptions,
+  pub theme: ThemeColour,
+  pub split: bool,
+  #[serde(default)]
+  pub output: ColourOutput,
+  // Render even if over the size limits.
+  #[serde(default)]
+  pub force: bool,
+}
+
+pub fn get_wip_patch_as_html(options: &ReqWipPatchCodeOptions) -> R<String> {
+  let ReqWipPatchCodeOptions {
+    wip,
+    theme,
+    split,
+    output,
+    force,
+  } = options;
+
+  let attributes = load_file_attributes(&wip.repo_path, &wip.patch.new_file);
+  let (hunks, _, _) = load_wip_hunks_with_attributes(wip, &attributes)?;
+
+  let (left, right) = if *split {
+    flatten_hunks_split(&hunks)
+  } else {
+    (flatten_hunks(hunks.clone()), Vec::new())
+  };
+
+  let stats = DiffStats::new(&hunks, left.len().max(right.len()));
+  if stats.too_large() && !*force {
+    return Ok(too_large_html(&stats));
+  }
+
+  let texts = load_wip_texts(wip, &attributes).ok();
+
+  let (old_text, new_text) = match texts {
+    Some(WipTexts { old, new, .. }) => (Some(old), Some(new)),
+    None => (None, None),
+  };
+
+  let mut colouring = COLOURING.write()?;
+  let mut c = colouring.get_colour_line_for_file(
+    theme,
+    &wip.repo_path,
+    wip.patch.get_file_path(),
+    first_line(&old_text, &new_text),
+  );
+  c.set_file_texts(old_text.as_deref(), new_text.as_deref());
+  c.output = *output;
+
+  Ok(render_lines(&hunks, &left, &right, *split, &mut c))
+}
+
+// Diffs bigger than either of these aren't rendered unless asked for with "force".
+const MAX_RENDER_LINES: usize = 20_000;
+const MAX_RENDER_BYTES: usize = 2_000_000;
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct DiffStats {
+  // Number of hunk lines including headers, i.e. the rows that will be rendered.
+  pub total_lines: usize,
+  pub added: usize,
+  pub removed: usize,
+  pub num_hunks: usize,
+  pub text_bytes: usize,
+}
+
+impl DiffStats {
+  pub fn new(hunks: &[Hunk], total_lines: usize) -> DiffStats {
+    let mut stats = DiffStats {
+      total_lines,
+      added: 0,
+      removed: 0,
+      num_hunks: hunks.len(),
+      text_bytes: 0,
+    };
+
+    for line in hunks.iter().flat_map(|h| h.lines.iter()) {
+      match line.status {
+        HunkLineStatus::Added => stats.added += 1,
+        HunkLineStatus::Removed => stats.removed += 1,
+        _ => {}
+      }
+      stats.text_bytes += line.text.len();
+    }
+
+    stats
+  }
+
+  pub fn too_large(&self) -> bool {
+    self.total_lines > MAX_RENDER_LINES || self.text_bytes > MAX_RENDER_BYTES
+  }
+}
+
+fn too_large_html(stats: &DiffStats) -> String {
+  div(
+    "tooLarge",
+    &f!(
+      "Diff too large to display: {} lines added, {} removed",
+      stats.added,
+      stats.removed
+    ),
+  )
+}
+
+#[derive(Debug, Deserialize, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct ReqPatchCodePageOptions {
+  // Its "force" also applies to pages.
+  pub code: ReqPatchCodeOptions,
+  // Index into the hunk lines (rows for split) to start from.
+  pub offset: usize,
+  pub limit: usize,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct PatchCodePage {
+  pub stats: DiffStats,
+  // None when the diff is too large and force isn't set.
+  pub html: Option<String>,
+  pub offset: usize,
+  pub has_more: bool,
+}
+
+pub fn get_patch_as_html_page(options: &ReqPatchCodePageOptions) -> R<PatchCodePage> {
+  let ReqPatchCodePageOptions {
+    code,
+    offset,
+    limit,
   } = options;
+  let ReqPatchCodeOptions {
+    repo_path,
+    commit,
+    patch,
+    theme,
+    split,
+    diff_options,
+    output,
+    force,
+  } = code;
+
+  let hunk_options = ReqHunkOptions {
+    repo_path: repo_path.clone(),
+    commit: commit.clone(),
+    patch: patch.clone(),
+    diff_options: diff_options.clone(),
+  };
+
+  let (hunks, left, right) = if *split {
+    load_hunks_split(&hunk_options)?
+  } else {
+    let (hunks, hunk_lines) = load_hunks(&hunk_options)?;
+    (hunks, hunk_lines, Vec::new())
+  };
+
+  let total_lines = left.len().max(right.len());
+  let stats = DiffStats::new(&hunks, total_lines);
+  let range = page_range(*offset, *limit, total_lines);
+  let has_more = range.end < total_lines;
+
+  if stats.too_large() && !*force {
+    return Ok(PatchCodePage {
+      stats,
+      html: None,
+      offset: *offset,
+      has_more,
+    });
+  }
+
+  let old_text = load_commit_side_text(repo_path, commit, patch, ContextSide::Old).ok();
+  let new_text = load_commit_side_text(repo_path, commit, patch, ContextSide::New).ok();
 
   let mut colouring = COLOURING.write()?;
-  let mut c = colouring.get_colour_line(theme, &patch.get_file_extension());
+  let mut c = colouring.get_colour_line_for_file(
+    theme,
+    repo_path,
+    patch.get_file_path(),
+    first_line(&old_text, &new_text),
+  );
+  c.set_file_texts(old_text.as_deref(), new_text.as_deref());
+  c.output = *output;
+
+  let html = if *split {
+    generate_lines_split_page(&left, &right, &hunks, &mut c, range)
+  } else {
+    generate_lines_page(&left, &hunks, &mut c, range)
+  };
+
+  Ok(PatchCodePage {
+    stats,
+    html: Some(html),
+    offset: *offset,
+    has_more,
+  })
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct ReqWipPatchCodePageOptions {
+  // Its "force" also applies to pages.
+  pub code: ReqWipPatchCodeOptions,
+  // Index into the hunk lines (rows for split) to start from.
+  pub offset: usize,
+  pub limit: usize,
+}
 
-  if *split {
-    let (hunks, left, right) = load_hunks_split(&ReqHunkOptions {
-      repo_path: repo_path.clone(),
-      commit: commit.clone(),
-      patch: patch.clone(),
-    })?;
+pub fn get_wip_patch_as_html_page(
+  options: &ReqWipPatchCodePageOptions,
+) -> R<PatchCodePage> {
+  let ReqWipPatchCodePageOptions {
+    code,
+    offset,
+    limit,
+  } = options;
+  let ReqWipPatchCodeOptions {
+    wip,
+    theme,
+    split,
+    output,
+    force,
+  } = code;
 
-    let lines = generate_lines_split(&left, &right, &hunks, &mut c);
+  let attributes = load_file_attributes(&wip.repo_path, &wip.patch.new_file);
+  let (hunks, _, _) = load_wip_hunks_with_attributes(wip, &attributes)?;
 
-    Ok(lines)
+  let (left, right) = if *split {
+    flatten_hunks_split(&hunks)
   } else {
-    let (hunks, hunk_lines) = load_hunks(&ReqHunkOptions {
-      repo_path: repo_path.clone(),
-      commit: commit.clone(),
-      patch: patch.clone(),
-    })?;
+    (flatten_hunks(hunks.clone()), Vec::new())
+  };
+
+  let total_lines = left.len().max(right.len());
+  let stats = DiffStats::new(&hunks, total_lines);
+  let range = page_range(*offset, *limit, total_lines);
+  let has_more = range.end < total_lines;
+
+  if stats.too_large() && !*force {
+    return Ok(PatchCodePage {
+      stats,
+      html: None,
+      offset: *offset,
+      has_more,
+    });
+  }
+
+  let (old_text, new_text) = match load_wip_texts(wip, &attributes).ok() {
+    Some(WipTexts { old, new, .. }) => (Some(old), Some(new)),
+    None => (None, None),
+  };
+
+  let mut colouring = COLOURING.write()?;
+  let mut c = colouring.get_colour_line_for_file(
+    theme,
+    &wip.repo_path,
+    wip.patch.get_file_path(),
+    first_line(&old_text, &new_text),
+  );
+  c.set_file_texts(old_text.as_deref(), new_text.as_deref());
+  c.output = *output;
+
+  let html = if *split {
+    generate_lines_split_page(&left, &right, &hunks, &mut c, range)
+  } else {
+    generate_lines_page(&left, &hunks, &mut c, range)
+  };
+
+  Ok(PatchCodePage {
+    stats,
+    html: Some(html),
+    offset: *offset,
+    has_more,
+  })
+}
+
+// Clamped to total, so an offset past the end gives an empty range.
+fn page_range(offset: usize, limit: usize, total: usize) -> Range<usize> {
+  let end = offset.saturating_add(limit).min(total);
+
+  offset.min(end)..end
+}
+
+#[derive(Debug, Deserialize, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct ReqDiffBetweenCodeOptions {
+  pub between: ReqHunksBetweenOptions,
+  pub theme: ThemeColour,
+  pub split: bool,
+  #[serde(default)]
+  pub output: ColourOutput,
+  // Render even if over the size limits.
+  #[serde(default)]
+  pub force: bool,
+}
+
+pub fn get_diff_between_as_html(options: &ReqDiffBetweenCodeOptions) -> R<String> {
+  let ReqDiffBetweenCodeOptions {
+    between,
+    theme,
+    split,
+    output,
+    force,
+  } = options;
+  let ReqHunksBetweenOptions {
+    repo_path,
+    from,
+    to,
+    three_dot,
+    patch,
+    ..
+  } = between;
 
-    let lines = generate_lines(&hunk_lines, &hunks, &mut c);
+  let (hunks, left, right) = if *split {
+    load_hunks_between_split(between)?
+  } else {
+    let (hunks, hunk_lines) = load_hunks_between(between)?;
+    (hunks, hunk_lines, Vec::new())
+  };
 
-    Ok(lines)
+  let stats = DiffStats::new(&hunks, left.len().max(right.len()));
+  if stats.too_large() && !*force {
+    return Ok(too_large_html(&stats));
   }
+
+  let old_revision = if *three_dot {
+    merge_base(repo_path, from, to).unwrap_or(from.clone())
+  } else {
+    from.clone()
+  };
+  let old_text = load_file_at_revision(repo_path, &old_revision, &patch.old_file).ok();
+  let new_text = load_file_at_revision(repo_path, to, &patch.new_file).ok();
+
+  let mut colouring = COLOURING.write()?;
+  let mut c = colouring.get_colour_line_for_file(
+    theme,
+    repo_path,
+    patch.get_file_path(),
+    first_line(&old_text, &new_text),
+  );
+  c.set_file_texts(old_text.as_deref(), new_text.as_deref());
+  c.output = *output;
+
+  Ok(render_lines(&hunks, &left, &right, *split, &mut c))
 }
 
-// Paginate if too large?
 fn generate_lines(
-  hunk_lines: &Vec<HunkLine>,
+  hunk_lines: &[HunkLine],
+  hunks: &[Hunk],
+  colour: &mut ColourLine,
+) -> String {
+  generate_lines_page(hunk_lines, hunks, colour, 0..hunk_lines.len())
+}
+
+fn generate_lines_page(
+  hunk_lines: &[HunkLine],
   hunks: &[Hunk],
   colour: &mut ColourLine,
+  range: Range<usize>,
 ) -> String {
   use HunkLineStatus::*;
 
   let mut margin = String::new();
   let mut lines = String::new();
 
+  // Use all lines, so the width is the same on every page.
   let margin_width = get_margin_width(hunk_lines);
 
-  for hunk_line in hunk_lines {
+  let end = range.end.min(hunk_lines.len());
+  let start = range.start.min(end);
+
+  if start > 0 {
+    colour.start_fragment();
+  }
+
+  for hunk_line in &hunk_lines[start..end] {
     match hunk_line.status {
       HeaderStart => {
         colour.end_fragment();
@@ -102,21 +534,38 @@ pub fn add_line(
   colour: &mut ColourLine,
 ) {
   use HunkLineStatus::*;
-  let text = if let Ok(parts) = colour.colour(&f!("{}\n", line.text)) {
-    build_line(parts, &colour.colouring.theme)
+  let warning_ranges: Vec<CharRange> =
+    line.warnings.iter().map(|w| w.range.clone()).collect();
+
+  let line_text = f!("{}\n", line.text);
+  let mut text = if let Ok(parts) = colour.colour_hunk_line(line, &line_text) {
+    build_line(parts, colour, &line.changed_ranges, &warning_ranges)
   } else {
-    escape_xml(&line.text.replace('\n', ""))
+    build_plain_line(
+      &line.text.replace('\n', ""),
+      &line.changed_ranges,
+      &warning_ranges,
+    )
   };
 
+  if line
+    .warnings
+    .iter()
+    .any(|w| w.kind == LineWarningKind::CrLf)
+  {
+    // language=HTML
+    text += "<span class='warning'>\\r</span>";
+  }
+
   match line.status {
     Added => {
-      *lines += &div("added", &text);
+      *lines += &div(&line_class("added", line), &text);
     }
     Removed => {
-      *lines += &div("removed", &text);
+      *lines += &div(&line_class("removed", line), &text);
     }
     Unchanged => {
-      *lines += &div("none", &text);
+      *lines += &div(&line_class("none", line), &text);
     }
     HeaderStart => {
       *lines += &div("headerStart", "");
@@ -134,21 +583,165 @@ pub fn add_line(
   }
 }
 
-fn build_line(parts: Vec<(Style, &str)>, theme: &ThemeColour) -> String {
+// Moved blocks alternate between two classes so neighbouring blocks can be told apart.
+fn line_class(status_class: &str, line: &HunkLine) -> String {
+  let mut class = match &line.moved {
+    Some(moved) => f!("{} moved zebra{}", status_class, moved.group % 2),
+    None => status_class.to_string(),
+  };
+
+  if !line.warnings.is_empty() {
+    class += " warning";
+  }
+
+  class
+}
+
+// changed_ranges parts are given the "changed" class, and warning_ranges the "warning"
+// class, on top of syntax colouring.
+pub fn build_line(
+  parts: Vec<Token>,
+  colour: &ColourLine,
+  changed_ranges: &[CharRange],
+  warning_ranges: &[CharRange],
+) -> String {
   let mut line = String::new();
+  let mut offset = 0;
+
+  for token in parts {
+    let text = token.text.replace('\n', "");
+
+    line += &match colour.output {
+      ColourOutput::Inline => {
+        let style = colour_to_style(token.style.foreground, &colour.colouring.theme);
+
+        build_segments(&text, offset, changed_ranges, warning_ranges, |class, t| {
+          match class {
+            // language=HTML
+            Some(class) => f!(
+              "<span class='{}' style='color: {};'>{}</span>",
+              class,
+              style,
+              t
+            ),
+            // language=HTML
+            None => f!("<span style='color: {};'>{}</span>", style, t),
+          }
+        })
+      }
+      ColourOutput::Classes => {
+        let token_class = colour.token_class(&token);
+
+        build_segments(&text, offset, changed_ranges, warning_ranges, |class, t| {
+          match class {
+            // language=HTML
+            Some(class) => f!("<span class='{} {}'>{}</span>", token_class, class, t),
+            // language=HTML
+            None => f!("<span class='{}'>{}</span>", token_class, t),
+          }
+        })
+      }
+    };
 
-  for (style, text) in parts {
-    line += &f!(
+    offset += text.chars().count();
+  }
+
+  line
+}
+
+fn build_plain_line(
+  text: &str,
+  changed_ranges: &[CharRange],
+  warning_ranges: &[CharRange],
+) -> String {
+  build_segments(
+    text,
+    0,
+    changed_ranges,
+    warning_ranges,
+    |class, t| match class {
       // language=HTML
-      "<span style='color: {};'>{}</span>",
-      colour_to_style(style.foreground, theme),
-      escape_xml(&text.replace('\n', ""))
-    );
+      Some(class) => f!("<span class='{}'>{}</span>", class, t),
+      None => t,
+    },
+  )
+}
+
+// Passes each escaped segment of text and its class (if any) to make_span.
+fn build_segments(
+  text: &str,
+  offset: usize,
+  changed_ranges: &[CharRange],
+  warning_ranges: &[CharRange],
+  make_span: impl Fn(Option<&str>, String) -> String,
+) -> String {
+  let mut line = String::new();
+  let mut segment_offset = offset;
+
+  for (segment, changed) in split_by_ranges(text, offset, changed_ranges) {
+    for (part, warning) in split_by_ranges(&segment, segment_offset, warning_ranges) {
+      let class = match (changed, warning) {
+        (true, true) => Some("changed warning"),
+        (true, false) => Some("changed"),
+        (false, true) => Some("warning"),
+        (false, false) => None,
+      };
+
+      let html = if warning {
+        show_invisible(&part)
+      } else {
+        escape_xml(&part)
+      };
+
+      line += &make_span(class, html);
+    }
+
+    segment_offset += segment.chars().count();
   }
 
   line
 }
 
+// Bidi and zero width chars are replaced with their code point so they can be seen.
+fn show_invisible(text: &str) -> String {
+  let mut html = String::new();
+
+  for c in text.chars() {
+    if is_bidi_control(c) || is_zero_width(c) {
+      html += &f!("[U+{:04X}]", c as u32);
+    } else {
+      html += &escape_xml(&c.to_string());
+    }
+  }
+
+  html
+}
+
+// Splits text starting at char offset into segments that are or aren't in ranges.
+fn split_by_ranges(
+  text: &str,
+  offset: usize,
+  ranges: &[CharRange],
+) -> Vec<(String, bool)> {
+  if ranges.is_empty() {
+    return vec![(text.to_string(), false)];
+  }
+
+  let mut segments: Vec<(String, bool)> = Vec::new();
+
+  for (i, c) in text.chars().enumerate() {
+    let pos = offset + i;
+    let changed = ranges.iter().any(|r| pos >= r.start && pos < r.end);
+
+    match segments.last_mut() {
+      Some((segment, last_changed)) if *last_changed == changed => segment.push(c),
+      _ => segments.push((c.to_string(), changed)),
+    }
+  }
+
+  segments
+}
+
 pub fn add_margin_line(margin: &mut String, line: &HunkLine, margin_width: usize) {
   use HunkLineStatus::*;
   let empty_space = make_spaces(margin_width);
@@ -233,7 +826,7 @@ pub fn div(class_name: &str, content: &str) -> String {
   f!("<div class='{}'>{}</div>", class_name, content)
 }
 
-fn escape_xml(line: &str) -> String {
+pub fn escape_xml(line: &str) -> String {
   line
     .replace('&', "&amp;")
     .replace('<', "&lt;")
@@ -256,7 +849,20 @@ pub fn make_spaces(len: usize) -> String {
 
 #[cfg(test)]
 mod tests {
-  use crate::git::queries::hunks::html_code::{calc_num_chars, make_spaces, pad_left};
+  use crate::git::git_types::{
+    CharRange, Commit, DateResult, Patch, PatchType, WipPatch, WipPatchType,
+  };
+  use crate::git::queries::hunks::html_code::{
+    calc_num_chars, get_diff_between_as_html, get_patch_as_html, get_patch_as_html_page,
+    get_wip_patch_as_html, get_wip_patch_as_html_page, make_spaces, pad_left, page_range,
+    show_invisible, split_by_ranges, DiffStats, ReqDiffBetweenCodeOptions,
+    ReqPatchCodeOptions, ReqPatchCodePageOptions, ReqWipPatchCodeOptions,
+    ReqWipPatchCodePageOptions, MAX_RENDER_BYTES, MAX_RENDER_LINES,
+  };
+  use crate::git::queries::hunks::load_hunks::ReqHunksBetweenOptions;
+  use crate::git::queries::syntax_colouring::ThemeColour;
+  use crate::git::queries::wip::wip_diff::{ReqWipHunksOptions, WipDiffKind};
+  use crate::git::test_repo::TestRepo;
 
   #[test]
   fn test_pad() {
@@ -264,9 +870,231 @@ mod tests {
     assert_eq!("   ", make_spaces(3));
   }
 
+  #[test]
+  fn test_split_by_ranges() {
+    let ranges = vec![CharRange { start: 3, end: 5 }];
+
+    assert_eq!(
+      split_by_ranges("abcdef", 1, &ranges),
+      vec![
+        ("ab".to_string(), false),
+        ("cd".to_string(), true),
+        ("ef".to_string(), false)
+      ]
+    );
+    assert_eq!(
+      split_by_ranges("xy", 10, &ranges),
+      vec![("xy".to_string(), false)]
+    );
+  }
+
+  #[test]
+  fn test_show_invisible() {
+    assert_eq!(show_invisible("a\u{202e}<"), "a[U+202E]&lt;");
+  }
+
   #[test]
   fn test_num_chars() {
     assert_eq!(1, calc_num_chars(0));
     assert_eq!(2, calc_num_chars(10));
   }
+
+  #[test]
+  fn test_diff_stats_too_large() {
+    let stats = |total_lines, text_bytes| DiffStats {
+      total_lines,
+      added: 0,
+      removed: 0,
+      num_hunks: 1,
+      text_bytes,
+    };
+
+    assert!(!stats(MAX_RENDER_LINES, MAX_RENDER_BYTES).too_large());
+    assert!(stats(MAX_RENDER_LINES + 1, 0).too_large());
+    assert!(stats(10, MAX_RENDER_BYTES + 1).too_large());
+  }
+
+  #[test]
+  fn test_page_range() {
+    assert_eq!(page_range(0, 10, 25), 0..10);
+    assert_eq!(page_range(20, 10, 25), 20..25);
+    assert_eq!(page_range(30, 10, 25), 25..25);
+    assert_eq!(page_range(5, usize::MAX, 25), 5..25);
+  }
+
+  fn code_options(
+    repo: &TestRepo,
+    commit_id: &str,
+    parent_id: &str,
+  ) -> ReqPatchCodeOptions {
+    ReqPatchCodeOptions {
+      repo_path: repo.path_str(),
+      commit: Commit {
+        author: String::new(),
+        email: String::new(),
+        date: DateResult {
+          ms: 0,
+          adjustment: 0,
+        },
+        id: commit_id.to_string(),
+        index: 0,
+        parent_ids: vec![parent_id.to_string()],
+        is_merge: false,
+        message: String::new(),
+        stash_id: String::new(),
+        refs: Vec::new(),
+        signature: None,
+        filtered: false,
+        num_skipped: 0,
+      },
+      patch: Patch {
+        commit_id: commit_id.to_string(),
+        old_file: "a.txt".to_string(),
+        new_file: "a.txt".to_string(),
+        patch_type: PatchType::M,
+        id: "a.txtM".to_string(),
+        is_image: false,
+      },
+      theme: ThemeColour::Dark,
+      split: false,
+      diff_options: Default::default(),
+      output: Default::default(),
+      force: false,
+    }
+  }
+
+  // Returns the parent and commit ids of a change too large to render.
+  fn large_diff_repo(repo: &TestRepo) -> (String, String) {
+    repo.write("a.txt", "a\n");
+    let parent_id = repo.commit("first");
+    let lines: String = (0..MAX_RENDER_LINES).map(|i| format!("{}\n", i)).collect();
+    repo.write("a.txt", format!("a\n{}", lines));
+    let commit_id = repo.commit("second");
+
+    (parent_id, commit_id)
+  }
+
+  #[test]
+  fn test_patch_html_size_limit_and_pages() {
+    let repo = TestRepo::new("patch_html_pages");
+    let (parent_id, commit_id) = large_diff_repo(&repo);
+
+    let mut options = code_options(&repo, &commit_id, &parent_id);
+
+    assert!(get_patch_as_html(&options).unwrap().contains("tooLarge"));
+
+    let page = get_patch_as_html_page(&ReqPatchCodePageOptions {
+      code: code_options(&repo, &commit_id, &parent_id),
+      offset: 0,
+      limit: 50,
+    })
+    .unwrap();
+    assert!(page.html.is_none());
+    assert!(page.stats.too_large());
+
+    options.force = true;
+    assert!(!get_patch_as_html(&options).unwrap().contains("tooLarge"));
+
+    let mut forced = code_options(&repo, &commit_id, &parent_id);
+    forced.force = true;
+    let page = get_patch_as_html_page(&ReqPatchCodePageOptions {
+      code: forced,
+      offset: 0,
+      limit: 50,
+    })
+    .unwrap();
+    assert!(page.html.is_some());
+    assert!(page.has_more);
+
+    let page = get_patch_as_html_page(&ReqPatchCodePageOptions {
+      code: options,
+      offset: usize::MAX - 1,
+      limit: 50,
+    })
+    .unwrap();
+    assert!(page.html.is_some());
+    assert!(!page.has_more);
+  }
+
+  #[test]
+  fn test_diff_between_html_size_limit() {
+    let repo = TestRepo::new("diff_between_html_size_limit");
+    let (parent_id, commit_id) = large_diff_repo(&repo);
+    let patch = code_options(&repo, &commit_id, &parent_id).patch;
+
+    let mut options = ReqDiffBetweenCodeOptions {
+      between: ReqHunksBetweenOptions {
+        repo_path: repo.path_str(),
+        from: parent_id,
+        to: commit_id,
+        three_dot: false,
+        patch,
+        diff_options: Default::default(),
+      },
+      theme: ThemeColour::Dark,
+      split: false,
+      output: Default::default(),
+      force: false,
+    };
+
+    assert!(get_diff_between_as_html(&options)
+      .unwrap()
+      .contains("tooLarge"));
+
+    options.force = true;
+    assert!(!get_diff_between_as_html(&options)
+      .unwrap()
+      .contains("tooLarge"));
+  }
+
+  #[test]
+  fn test_wip_html_size_limit_and_pages() {
+    let repo = TestRepo::new("wip_html_pages");
+    repo.write("a.txt", "a\n");
+    let head = repo.commit("first");
+    let lines: String = (0..MAX_RENDER_LINES).map(|i| format!("{}\n", i)).collect();
+    repo.write("a.txt", format!("a\n{}", lines));
+
+    let mut options = ReqWipPatchCodeOptions {
+      wip: ReqWipHunksOptions {
+        repo_path: repo.path_str(),
+        patch: WipPatch {
+          old_file: "a.txt".to_string(),
+          new_file: "a.txt".to_string(),
+          patch_type: WipPatchType::M,
+          staged_type: WipPatchType::Empty,
+          un_staged_type: WipPatchType::M,
+          conflicted: false,
+          id: "a.txt".to_string(),
+          is_image: false,
+          is_binary: false,
+        },
+        head_commit: Some(head),
+        diff_options: Default::default(),
+        kind: WipDiffKind::Unstaged,
+      },
+      theme: ThemeColour::Dark,
+      split: false,
+      output: Default::default(),
+      force: false,
+    };
+
+    assert!(get_wip_patch_as_html(&options)
+      .unwrap()
+      .contains("tooLarge"));
+
+    options.force = true;
+    assert!(!get_wip_patch_as_html(&options)
+      .unwrap()
+      .contains("tooLarge"));
+
+    let page = get_wip_patch_as_html_page(&ReqWipPatchCodePageOptions {
+      code: options,
+      offset: 0,
+      limit: 50,
+    })
+    .unwrap();
+    assert!(page.html.is_some());
+    assert!(page.has_more);
+  }
 }
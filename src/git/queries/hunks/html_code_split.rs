@@ -4,20 +4,40 @@ use crate::git::queries::hunks::html_code::{
   add_line, div, get_margin_width, make_spaces, pad_left, s,
 };
 use crate::git::queries::syntax_colouring::ColourLine;
+use std::ops::Range;
 
 pub fn generate_lines_split(
   hl_left: &[HunkLine],
   hl_right: &[HunkLine],
   hunks: &[Hunk],
   colour: &mut ColourLine,
+) -> String {
+  let len = hl_left.len().max(hl_right.len());
+
+  generate_lines_split_page(hl_left, hl_right, hunks, colour, 0..len)
+}
+
+// Both sides are the same length (or empty), so the range applies to both.
+pub fn generate_lines_split_page(
+  hl_left: &[HunkLine],
+  hl_right: &[HunkLine],
+  hunks: &[Hunk],
+  colour: &mut ColourLine,
+  range: Range<usize>,
 ) -> String {
   println!("{} {}", hl_left.len(), hl_right.len());
   let char_width = get_longest_line(hl_left, hl_right);
 
-  let (left_margin, left_lines) =
-    gen_side(hl_left, hunks, colour, Side::Left, char_width);
+  let (left_margin, left_lines) = gen_side(
+    hl_left,
+    hunks,
+    colour,
+    Side::Left,
+    char_width,
+    range.clone(),
+  );
   let (right_margin, right_lines) =
-    gen_side(hl_right, hunks, colour, Side::Right, char_width);
+    gen_side(hl_right, hunks, colour, Side::Right, char_width, range);
 
   let mut left = div("margin", &left_margin);
   left += &div("code", &left_lines);
@@ -40,15 +60,24 @@ fn gen_side(
   colour: &mut ColourLine,
   side: Side,
   char_width: usize,
+  range: Range<usize>,
 ) -> (String, String) {
   use HunkLineStatus::*;
 
+  // Use all lines, so the width is the same on every page.
   let margin_width = get_margin_width(hunk_lines);
 
   let mut margin = String::new();
   let mut lines = String::new();
 
-  for line in hunk_lines {
+  let end = range.end.min(hunk_lines.len());
+  let start = range.start.min(end);
+
+  if start > 0 {
+    colour.start_fragment();
+  }
+
+  for line in &hunk_lines[start..end] {
     let hunk = line.get_hunk(hunks);
 
     match &line.status {
@@ -130,6 +130,9 @@ impl HunkLine {
       text: line.text,
       index,
       line_ending: line.line_ending,
+      changed_ranges: Vec::new(),
+      moved: None,
+      warnings: Vec::new(),
     }
   }
 
@@ -142,6 +145,9 @@ impl HunkLine {
       text: String::from(""),
       index: 0,
       line_ending: String::from("\n"),
+      changed_ranges: Vec::new(),
+      moved: None,
+      warnings: Vec::new(),
     }
   }
 
@@ -154,6 +160,9 @@ impl HunkLine {
       text: String::from(""),
       index: 0,
       line_ending: String::from("\n"),
+      changed_ranges: Vec::new(),
+      moved: None,
+      warnings: Vec::new(),
     }
   }
 }
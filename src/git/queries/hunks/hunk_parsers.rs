@@ -2,13 +2,15 @@ use crate::git::git_types::{Hunk, HunkLine, HunkLineStatus, HunkRange};
 use crate::git::queries::hunks::hunk_line_parsers::{
   Line, P_HUNK_LINES, P_HUNK_LINE_RANGES,
 };
+use crate::git::queries::hunks::inline_changes::add_inline_changes;
+use crate::git::queries::hunks::line_warnings::{add_line_warnings, WhitespaceRules};
 use crate::parser::standard_parsers::{UNTIL_LINE_END, WS};
 use crate::parser::Parser;
 use crate::{and, many, map, map2, or, word};
 
 type IgnoredLine<'a> = (&'a str, String);
 
-const P_DIFF_LINE: Parser<IgnoredLine> = and!(word!("diff"), UNTIL_LINE_END);
+pub const P_DIFF_LINE: Parser<IgnoredLine> = and!(word!("diff"), UNTIL_LINE_END);
 
 const P_OPTIONAL_HEADER: Parser<String> = or!(
   map2!(and!(word!("deleted"), UNTIL_LINE_END), __, {
@@ -40,9 +42,9 @@ const P_RENAME_HEADER: Parser<String> = map2!(
 
 const P_INDEX_LINE: Parser<IgnoredLine> = and!(word!("index"), UNTIL_LINE_END);
 
-const P_OLD_FILE: Parser<IgnoredLine> = and!(word!("---"), UNTIL_LINE_END);
+pub const P_OLD_FILE: Parser<IgnoredLine> = and!(word!("---"), UNTIL_LINE_END);
 
-const P_NEW_FILE: Parser<IgnoredLine> = and!(word!("+++"), UNTIL_LINE_END);
+pub const P_NEW_FILE: Parser<IgnoredLine> = and!(word!("+++"), UNTIL_LINE_END);
 
 const P_BINARY_INFO: Parser<IgnoredLine> = and!(word!("Binary"), UNTIL_LINE_END);
 
@@ -64,7 +66,7 @@ const P_DIFF_HEADER: Parser<FileInfo> = map2!(
   res.3
 );
 
-const P_HUNK: Parser<Hunk> = map2!(
+pub const P_HUNK: Parser<Hunk> = map2!(
   and!(P_HUNK_LINE_RANGES, UNTIL_LINE_END, P_HUNK_LINES),
   res,
   {
@@ -103,30 +105,41 @@ pub const P_HUNKS: Parser<Vec<Hunk>> = map!(
       }];
     }
 
-    res
-      .1
-      .into_iter()
-      .enumerate()
-      .map(|(i, mut hunk)| {
-        let index = i as i32;
-
-        hunk.index = index;
-
-        hunk.lines = hunk
-          .lines
-          .into_iter()
-          .map(|mut line| {
-            line.hunk_index = index;
-            line
-          })
-          .collect();
-
-        hunk
-      })
-      .collect()
+    let mut hunks = set_hunk_indices(res.1);
+
+    for hunk in &mut hunks {
+      add_inline_changes(&mut hunk.lines);
+      // Loaders apply the repo's core.whitespace if it's set.
+      add_line_warnings(&mut hunk.lines, &WhitespaceRules::default());
+    }
+
+    hunks
   }
 );
 
+pub fn set_hunk_indices(hunks: Vec<Hunk>) -> Vec<Hunk> {
+  hunks
+    .into_iter()
+    .enumerate()
+    .map(|(i, mut hunk)| {
+      let index = i as i32;
+
+      hunk.index = index;
+
+      hunk.lines = hunk
+        .lines
+        .into_iter()
+        .map(|mut line| {
+          line.hunk_index = index;
+          line
+        })
+        .collect();
+
+      hunk
+    })
+    .collect()
+}
+
 fn get_hunk_lines(old_num: i32, new_num: i32, lines: Vec<Line>) -> Vec<HunkLine> {
   let mut old_num = old_num;
   let mut new_num = new_num;
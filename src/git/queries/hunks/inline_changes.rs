@@ -0,0 +1,195 @@
+use similar::{capture_diff_slices, Algorithm, DiffTag};
+
+use crate::git::git_types::{CharRange, HunkLine, HunkLineStatus};
+
+// Diffing very long lines (e.g. minified code) isn't useful.
+const MAX_LINE_LENGTH: usize = 2000;
+
+/*
+Finds runs of removed lines followed by added lines, pairs them up in order, and sets
+changed_ranges on each pair. Lines that are too different are left alone, otherwise
+everything would be highlighted.
+ */
+pub fn add_inline_changes(lines: &mut [HunkLine]) {
+  use HunkLineStatus::*;
+
+  let mut i = 0;
+
+  while i < lines.len() {
+    if lines[i].status != Removed {
+      i += 1;
+      continue;
+    }
+
+    let removed_start = i;
+    while i < lines.len() && lines[i].status == Removed {
+      i += 1;
+    }
+    let added_start = i;
+    while i < lines.len() && lines[i].status == Added {
+      i += 1;
+    }
+
+    let num_pairs = (added_start - removed_start).min(i - added_start);
+
+    for n in 0..num_pairs {
+      let old = removed_start + n;
+      let new = added_start + n;
+
+      if let Some((old_ranges, new_ranges)) =
+        calc_changed_ranges(&lines[old].text, &lines[new].text)
+      {
+        lines[old].changed_ranges = old_ranges;
+        lines[new].changed_ranges = new_ranges;
+      }
+    }
+  }
+}
+
+type ChangedRanges = (Vec<CharRange>, Vec<CharRange>);
+
+fn calc_changed_ranges(old: &str, new: &str) -> Option<ChangedRanges> {
+  if old.len() > MAX_LINE_LENGTH || new.len() > MAX_LINE_LENGTH {
+    return None;
+  }
+
+  let old_tokens = tokenise(old);
+  let new_tokens = tokenise(new);
+
+  let old_words: Vec<&str> = old_tokens.iter().map(|t| t.2).collect();
+  let new_words: Vec<&str> = new_tokens.iter().map(|t| t.2).collect();
+
+  let ops = capture_diff_slices(Algorithm::Myers, &old_words, &new_words);
+
+  let mut old_ranges = Vec::new();
+  let mut new_ranges = Vec::new();
+  let mut unchanged_chars = 0;
+
+  for op in ops {
+    let (tag, old_range, new_range) = op.as_tag_tuple();
+
+    if tag == DiffTag::Equal {
+      unchanged_chars += old_tokens[old_range]
+        .iter()
+        .map(|t| t.1 - t.0)
+        .sum::<usize>();
+      continue;
+    }
+
+    if !old_range.is_empty() {
+      push_range(
+        &mut old_ranges,
+        old_tokens[old_range.start].0,
+        old_tokens[old_range.end - 1].1,
+      );
+    }
+    if !new_range.is_empty() {
+      push_range(
+        &mut new_ranges,
+        new_tokens[new_range.start].0,
+        new_tokens[new_range.end - 1].1,
+      );
+    }
+  }
+
+  let total_chars = old.chars().count() + new.chars().count();
+
+  // Less than half the same.
+  if unchanged_chars * 4 < total_chars {
+    return None;
+  }
+
+  Some((old_ranges, new_ranges))
+}
+
+// Join with the previous range if they touch.
+fn push_range(ranges: &mut Vec<CharRange>, start: usize, end: usize) {
+  if let Some(last) = ranges.last_mut() {
+    if last.end == start {
+      last.end = end;
+      return;
+    }
+  }
+
+  ranges.push(CharRange { start, end });
+}
+
+#[derive(PartialEq)]
+enum CharType {
+  Word,
+  Space,
+  Other,
+}
+
+fn char_type(c: char) -> CharType {
+  if c.is_alphanumeric() || c == '_' {
+    CharType::Word
+  } else if c.is_whitespace() {
+    CharType::Space
+  } else {
+    CharType::Other
+  }
+}
+
+// Words and runs of whitespace, with each other char as its own token.
+// Returns (start char, end char, text).
+fn tokenise(text: &str) -> Vec<(usize, usize, &str)> {
+  let mut tokens = Vec::new();
+  let mut start_byte = 0;
+  let mut start_char = 0;
+  let mut prev_type: Option<CharType> = None;
+
+  for (char_i, (byte_i, c)) in text.char_indices().enumerate() {
+    let t = char_type(c);
+
+    let join = match &prev_type {
+      Some(prev) => *prev == t && t != CharType::Other,
+      None => true,
+    };
+
+    if !join {
+      tokens.push((start_char, char_i, &text[start_byte..byte_i]));
+      start_byte = byte_i;
+      start_char = char_i;
+    }
+
+    prev_type = Some(t);
+  }
+
+  if start_byte < text.len() {
+    tokens.push((start_char, text.chars().count(), &text[start_byte..]));
+  }
+
+  tokens
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::git::git_types::CharRange;
+  use crate::git::queries::hunks::inline_changes::{calc_changed_ranges, tokenise};
+
+  #[test]
+  fn test_tokenise() {
+    let tokens: Vec<&str> = tokenise("let a_b = f(1);").iter().map(|t| t.2).collect();
+
+    assert_eq!(
+      tokens,
+      vec!["let", " ", "a_b", " ", "=", " ", "f", "(", "1", ")", ";"]
+    );
+  }
+
+  #[test]
+  fn test_calc_changed_ranges() {
+    let (old, new) =
+      calc_changed_ranges("let total = count + 1;", "let total = count + 2;").unwrap();
+
+    assert_eq!(old, vec![CharRange { start: 20, end: 21 }]);
+    assert_eq!(new, vec![CharRange { start: 20, end: 21 }]);
+
+    // Multibyte chars are counted as one.
+    let (_, new) = calc_changed_ranges("é = 1", "é = 22").unwrap();
+    assert_eq!(new, vec![CharRange { start: 4, end: 6 }]);
+
+    assert!(calc_changed_ranges("abc def", "xyz uvw").is_none());
+  }
+}
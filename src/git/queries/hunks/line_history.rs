@@ -0,0 +1,299 @@
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::git::git_types::{Commit, Hunk, HunkLine};
+use crate::git::queries::file_history::load_commits_by_id;
+use crate::git::queries::hunks::hunk_parsers::{
+  set_hunk_indices, P_DIFF_LINE, P_HUNK, P_NEW_FILE, P_OLD_FILE,
+};
+use crate::git::queries::hunks::inline_changes::add_inline_changes;
+use crate::git::queries::hunks::line_warnings::{add_line_warnings, WhitespaceRules};
+use crate::git::queries::hunks::load_hunks::flatten_hunks;
+use crate::git::run_git::{run_git_err, RunGitOptions};
+use crate::parser::standard_parsers::WS;
+use crate::parser::{parse_all_err, Parser};
+use crate::server::request_util::{ES, R};
+use crate::{and, f, many, map, map2, or, until_str};
+
+#[derive(Debug, Clone, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub enum LineRange {
+  // 1 based, inclusive.
+  Lines { start: u32, end: u32 },
+  // Git finds the function by name using the diff driver's funcname pattern.
+  Function { name: String },
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct ReqLineHistoryOptions {
+  pub repo_path: String,
+  // Path of the file at start_commit_id.
+  pub file_path: String,
+  // Defaults to HEAD. Line numbers are relative to this version of the file.
+  pub start_commit_id: Option<String>,
+  pub range: LineRange,
+  pub skip: u32,
+  pub num: u32,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct LineHistory {
+  pub entries: Vec<LineHistoryEntry>,
+  pub has_more: bool,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct LineHistoryEntry {
+  pub commit: Commit,
+  // The range can move between files if they were renamed.
+  pub old_file: String,
+  pub new_file: String,
+  pub hunks: Vec<Hunk>,
+  pub hunk_lines: Vec<HunkLine>,
+}
+
+pub fn load_line_history(options: &ReqLineHistoryOptions) -> R<LineHistory> {
+  let ReqLineHistoryOptions {
+    repo_path,
+    file_path,
+    start_commit_id,
+    range,
+    skip,
+    num,
+  } = options;
+
+  let range_arg = match range {
+    LineRange::Lines { start, end } => f!("-L{},{}:{}", start, end, file_path),
+    LineRange::Function { name } => f!("-L:{}:{}", name, file_path),
+  };
+
+  let out = run_git_err(RunGitOptions {
+    repo_path,
+    args: [
+      f!("log"),
+      range_arg,
+      f!("--format=%H,"),
+      f!("--no-color"),
+      // diff_file_name expects these, whatever diff.noprefix or diff.mnemonicPrefix say.
+      f!("--src-prefix=a/"),
+      f!("--dst-prefix=b/"),
+      f!("--skip={}", skip),
+      // Load one extra to find out if there are more.
+      f!("-n{}", num + 1),
+      start_commit_id.clone().unwrap_or(f!("HEAD")),
+    ],
+  })?;
+
+  // E.g. the function name wasn't found.
+  if out.stdout.is_empty() && !out.stderr.is_empty() {
+    return Err(ES::from(out.stderr.trim()));
+  }
+
+  let mut parsed = parse_all_err(P_LINE_LOG, &out.stdout)?;
+
+  let has_more = parsed.len() > *num as usize;
+  parsed.truncate(*num as usize);
+
+  let ids: Vec<&String> = parsed.iter().map(|c| &c.commit_id).collect();
+  let commits = load_commits_by_id(repo_path, &ids)?;
+  let rules = WhitespaceRules::from_config(repo_path).unwrap_or_default();
+
+  let entries = parsed
+    .into_iter()
+    .filter_map(|c| {
+      let commit = commits.get(&c.commit_id)?.clone();
+      let mut hunks = set_hunk_indices(c.hunks);
+
+      for hunk in &mut hunks {
+        add_inline_changes(&mut hunk.lines);
+        add_line_warnings(&mut hunk.lines, &rules);
+      }
+
+      Some(LineHistoryEntry {
+        commit,
+        old_file: c.old_file,
+        new_file: c.new_file,
+        hunk_lines: flatten_hunks(hunks.clone()),
+        hunks,
+      })
+    })
+    .collect();
+
+  Ok(LineHistory { entries, has_more })
+}
+
+struct LineLogCommit {
+  commit_id: String,
+  // Empty when the commit has no diff.
+  old_file: String,
+  new_file: String,
+  hunks: Vec<Hunk>,
+}
+
+struct LineLogDiff {
+  old_file: String,
+  new_file: String,
+  hunks: Vec<Hunk>,
+}
+
+type PLineLogDiffResult<'a> = (
+  (&'a str, String),
+  (&'a str, String),
+  (&'a str, String),
+  Vec<Hunk>,
+);
+
+/*
+Output of "git log -L" with our format looks like this. Unlike a normal diff there's no
+index line, and renames and new files don't get extra header lines.
+
+5fc9b796d1789596ce26a2d04d5e2509a99556d5,
+
+diff --git a/g.rs b/g.rs
+--- a/g.rs
++++ b/g.rs
+@@ -4,2 +4,3 @@
+ fn b() {
+-  2
++  3
+
+Merges that changed the range themselves are listed without a diff.
+ */
+const P_LINE_LOG_DIFF: Parser<LineLogDiff> = map!(
+  and!(P_DIFF_LINE, P_OLD_FILE, P_NEW_FILE, many!(P_HUNK)),
+  |res: PLineLogDiffResult| {
+    LineLogDiff {
+      old_file: diff_file_name(&res.1 .1),
+      new_file: diff_file_name(&res.2 .1),
+      hunks: res.3,
+    }
+  }
+);
+
+const P_LINE_LOG_COMMIT: Parser<LineLogCommit> = map!(
+  and!(
+    WS,
+    until_str!(","),
+    WS,
+    or!(map2!(P_LINE_LOG_DIFF, res, Some(res)), map2!(WS, __, None))
+  ),
+  |res: (String, String, String, Option<LineLogDiff>)| {
+    let (old_file, new_file, hunks) = match res.3 {
+      Some(diff) => (diff.old_file, diff.new_file, diff.hunks),
+      None => (String::new(), String::new(), Vec::new()),
+    };
+
+    LineLogCommit {
+      commit_id: res.1,
+      old_file,
+      new_file,
+      hunks,
+    }
+  }
+);
+
+const P_LINE_LOG: Parser<Vec<LineLogCommit>> = many!(P_LINE_LOG_COMMIT);
+
+// " a/src/main.rs" -> "src/main.rs". Empty for "/dev/null".
+fn diff_file_name(name: &str) -> String {
+  let name = name.trim();
+
+  if name == "/dev/null" {
+    return String::new();
+  }
+
+  name
+    .strip_prefix("a/")
+    .or_else(|| name.strip_prefix("b/"))
+    .unwrap_or(name)
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::git::queries::hunks::line_history::P_LINE_LOG;
+  use crate::parser::parse_all;
+
+  #[test]
+  fn test_p_line_log() {
+    let out = "5fc9b796d1789596ce26a2d04d5e2509a99556d5,
+
+diff --git a/g.rs b/g.rs
+--- a/g.rs
++++ b/g.rs
+@@ -4,2 +4,3 @@
+ fn b() {
+-  2
++  3
++  4
+eff2ac7b8b8b6e204a495d3fe24ab15847486bda,
+
+diff --git a/f.rs b/g.rs
+--- a/f.rs
++++ b/g.rs
+@@ -1,4 +1,5 @@
+ a
+ B
+ c
+ d
++x
+b8aff6b055542fe5751e2515d09f0608f972cfff,
+
+diff --git a/f.rs b/f.rs
+--- /dev/null
++++ b/f.rs
+@@ -0,0 +1,3 @@
++a
++b
++c
+";
+
+    let commits = parse_all(P_LINE_LOG, out);
+
+    assert!(commits.is_some());
+    let commits = commits.unwrap();
+
+    assert_eq!(commits.len(), 3);
+    assert_eq!(commits[0].hunks[0].lines.len(), 4);
+    assert_eq!(commits[1].old_file, "f.rs");
+    assert_eq!(commits[1].new_file, "g.rs");
+    assert_eq!(commits[2].old_file, "");
+    assert_eq!(commits[2].hunks[0].lines.len(), 3);
+  }
+
+  #[test]
+  fn test_p_line_log_merge_without_diff() {
+    let out = "1697b795209d4e2fb790dac6949a6cbb068cbcf9,
+
+a9033c931ee74306457fabf2b9e36ad5dfef3f98,
+
+diff --git a/f b/f
+--- a/f
++++ b/f
+@@ -1,3 +1,3 @@
+ 1
+-2
++X
+ 3
+";
+
+    let commits = parse_all(P_LINE_LOG, out).unwrap();
+
+    assert_eq!(commits.len(), 2);
+    assert_eq!(
+      commits[0].commit_id,
+      "1697b795209d4e2fb790dac6949a6cbb068cbcf9"
+    );
+    assert!(commits[0].hunks.is_empty());
+    assert_eq!(commits[0].new_file, "");
+    assert_eq!(commits[1].new_file, "f");
+    assert_eq!(commits[1].hunks[0].lines.len(), 4);
+  }
+}
@@ -0,0 +1,249 @@
+use crate::git::git_types::{
+  CharRange, HunkLine, HunkLineStatus, LineWarning, LineWarningKind,
+};
+use crate::git::queries::config::read_config_value;
+
+// Which whitespace errors to look for, from core.whitespace.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WhitespaceRules {
+  pub blank_at_eol: bool,
+  pub space_before_tab: bool,
+  pub indent_with_non_tab: bool,
+  pub tab_in_indent: bool,
+  // CR at the end of a line is part of the line ending, so CRLF isn't an error.
+  pub cr_at_eol: bool,
+  pub tab_width: usize,
+}
+
+// Git's defaults. blank-at-eof is on by default too, but hunks don't know where the
+// file ends.
+impl Default for WhitespaceRules {
+  fn default() -> Self {
+    Self {
+      blank_at_eol: true,
+      space_before_tab: true,
+      indent_with_non_tab: false,
+      tab_in_indent: false,
+      cr_at_eol: false,
+      tab_width: 8,
+    }
+  }
+}
+
+impl WhitespaceRules {
+  // E.g. "-space-before-tab,tab-in-indent,tabwidth=4". Unknown names are ignored.
+  pub fn parse(value: &str) -> WhitespaceRules {
+    let mut rules = WhitespaceRules::default();
+
+    for name in value.split(',').map(|n| n.trim()) {
+      let (on, name) = match name.strip_prefix('-') {
+        Some(name) => (false, name),
+        None => (true, name),
+      };
+
+      match name {
+        "blank-at-eol" | "trailing-space" => rules.blank_at_eol = on,
+        "space-before-tab" => rules.space_before_tab = on,
+        "indent-with-non-tab" => rules.indent_with_non_tab = on,
+        "tab-in-indent" => rules.tab_in_indent = on,
+        "cr-at-eol" => rules.cr_at_eol = on,
+        _ => {
+          if let Some(width) = name.strip_prefix("tabwidth=") {
+            if let Ok(width) = width.parse::<usize>() {
+              rules.tab_width = width.clamp(1, 63);
+            }
+          }
+        }
+      }
+    }
+
+    rules
+  }
+
+  // None if core.whitespace isn't set, in which case the defaults already apply.
+  pub fn from_config(repo_path: &str) -> Option<WhitespaceRules> {
+    read_config_value(repo_path, "core.whitespace").map(|value| Self::parse(&value))
+  }
+}
+
+/*
+Sets warnings on each line, replacing any already there. CRLF is only a warning on
+added lines when other lines in the slice end in LF, i.e. it was introduced into an LF
+file.
+ */
+pub fn add_line_warnings(lines: &mut [HunkLine], rules: &WhitespaceRules) {
+  let has_lf = lines
+    .iter()
+    .any(|l| is_code_line(l) && l.line_ending == "\n");
+
+  for line in lines.iter_mut() {
+    line.warnings = if is_code_line(line) {
+      calc_line_warnings(line, rules, has_lf)
+    } else {
+      Vec::new()
+    };
+  }
+}
+
+fn is_code_line(line: &HunkLine) -> bool {
+  use HunkLineStatus::*;
+
+  matches!(line.status, Added | Removed | Unchanged)
+}
+
+fn calc_line_warnings(
+  line: &HunkLine,
+  rules: &WhitespaceRules,
+  file_has_lf: bool,
+) -> Vec<LineWarning> {
+  use LineWarningKind::*;
+
+  let mut warnings = Vec::new();
+  let chars: Vec<char> = line.text.chars().collect();
+
+  for (i, c) in chars.iter().enumerate() {
+    // A byte order mark is expected at the start of a file.
+    let is_bom = *c == '\u{feff}' && i == 0 && line.new_num.or(line.old_num) == Some(1);
+
+    let kind = if is_bidi_control(*c) {
+      Some(BidiControl)
+    } else if is_zero_width(*c) && !is_bom {
+      Some(ZeroWidth)
+    } else {
+      None
+    };
+
+    if let Some(kind) = kind {
+      warnings.push(warning(kind, i, i + 1));
+    }
+  }
+
+  // Like git, whitespace is only checked on lines being added.
+  if line.status != HunkLineStatus::Added {
+    return warnings;
+  }
+
+  let indent_len = chars
+    .iter()
+    .take_while(|c| **c == ' ' || **c == '\t')
+    .count();
+  let indent = &chars[..indent_len];
+
+  if rules.space_before_tab {
+    if let Some(last_tab) = indent.iter().rposition(|c| *c == '\t') {
+      if let Some(first_space) = indent[..last_tab].iter().position(|c| *c == ' ') {
+        warnings.push(warning(SpaceBeforeTab, first_space, last_tab + 1));
+      }
+    }
+  }
+
+  if rules.indent_with_non_tab {
+    let leading_spaces = indent.iter().take_while(|c| **c == ' ').count();
+
+    if leading_spaces >= rules.tab_width {
+      warnings.push(warning(IndentWithNonTab, 0, leading_spaces));
+    }
+  }
+
+  if rules.tab_in_indent && indent.contains(&'\t') {
+    warnings.push(warning(TabInIndent, 0, indent_len));
+  }
+
+  if rules.blank_at_eol {
+    let trailing = chars.iter().rev().take_while(|c| c.is_whitespace()).count();
+
+    if trailing > 0 {
+      warnings.push(warning(
+        TrailingWhitespace,
+        chars.len() - trailing,
+        chars.len(),
+      ));
+    }
+  }
+
+  if !rules.cr_at_eol && file_has_lf && line.line_ending == "\r\n" {
+    warnings.push(warning(CrLf, chars.len(), chars.len()));
+  }
+
+  warnings
+}
+
+fn warning(kind: LineWarningKind, start: usize, end: usize) -> LineWarning {
+  LineWarning {
+    kind,
+    range: CharRange { start, end },
+  }
+}
+
+pub fn is_bidi_control(c: char) -> bool {
+  matches!(
+    c,
+    '\u{061c}' | '\u{200e}' | '\u{200f}' | '\u{202a}'..='\u{202e}' | '\u{2066}'..='\u{2069}'
+  )
+}
+
+pub fn is_zero_width(c: char) -> bool {
+  matches!(c, '\u{200b}'..='\u{200d}' | '\u{2060}' | '\u{feff}')
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::git::git_types::{HunkLine, HunkLineStatus, LineWarningKind};
+  use crate::git::queries::hunks::line_warnings::{add_line_warnings, WhitespaceRules};
+
+  fn line(status: HunkLineStatus, text: &str, line_ending: &str) -> HunkLine {
+    HunkLine {
+      status,
+      old_num: None,
+      new_num: Some(2),
+      hunk_index: 0,
+      text: text.to_string(),
+      index: 0,
+      line_ending: line_ending.to_string(),
+      changed_ranges: Vec::new(),
+      moved: None,
+      warnings: Vec::new(),
+    }
+  }
+
+  fn kinds(line: &HunkLine) -> Vec<LineWarningKind> {
+    line.warnings.iter().map(|w| w.kind).collect()
+  }
+
+  #[test]
+  fn test_parse_rules() {
+    let rules = WhitespaceRules::parse("-trailing-space, tab-in-indent,tabwidth=4");
+
+    assert!(!rules.blank_at_eol);
+    assert!(rules.tab_in_indent);
+    assert!(rules.space_before_tab);
+    assert_eq!(rules.tab_width, 4);
+  }
+
+  #[test]
+  fn test_add_line_warnings() {
+    use HunkLineStatus::*;
+    use LineWarningKind::*;
+
+    let mut lines = vec![
+      line(Unchanged, "a", "\n"),
+      line(Added, "b  ", "\n"),
+      line(Added, " \tc", "\r\n"),
+      line(Removed, "d  ", "\n"),
+      line(Removed, "if x \u{202e}{", "\n"),
+    ];
+
+    add_line_warnings(&mut lines, &WhitespaceRules::default());
+
+    assert!(lines[0].warnings.is_empty());
+    assert_eq!(kinds(&lines[1]), vec![TrailingWhitespace]);
+    assert_eq!(lines[1].warnings[0].range.start, 1);
+    assert_eq!(kinds(&lines[2]), vec![SpaceBeforeTab, CrLf]);
+    assert!(lines[3].warnings.is_empty());
+    assert_eq!(kinds(&lines[4]), vec![BidiControl]);
+    assert_eq!(lines[4].warnings[0].range.start, 5);
+
+    add_line_warnings(&mut lines, &WhitespaceRules::parse("cr-at-eol"));
+    assert_eq!(kinds(&lines[2]), vec![SpaceBeforeTab]);
+  }
+}
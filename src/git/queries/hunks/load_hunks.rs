@@ -1,6 +1,11 @@
 use crate::f;
 use crate::git::git_types::{Commit, Hunk, HunkLine, HunkLineStatus, Patch, PatchType};
+use crate::git::queries::hunks::diff_options::DiffOptions;
 use crate::git::queries::hunks::hunk_parsers::P_HUNKS;
+use crate::git::queries::hunks::line_warnings::{add_line_warnings, WhitespaceRules};
+use crate::git::queries::hunks::moved_lines::{
+  detect_moved_lines, detect_moved_lines_in_file, FileHunks,
+};
 use crate::git::queries::COMMIT_0_ID;
 use crate::git::run_git;
 use crate::git::run_git::{GitOut, RunGitOptions};
@@ -16,15 +21,22 @@ pub struct ReqHunkOptions {
   pub repo_path: String,
   pub commit: Commit,
   pub patch: Patch,
+  #[serde(default)]
+  pub diff_options: DiffOptions,
 }
 
 pub fn load_hunks(options: &ReqHunkOptions) -> R<(Vec<Hunk>, Vec<HunkLine>)> {
   let GitOut { stdout, .. } = run_git::run_git_err(RunGitOptions {
     repo_path: &options.repo_path,
-    args: load_hunks_args(&options.commit, &options.patch),
+    args: load_hunks_args(&options.commit, &options.patch, &options.diff_options),
   })?;
 
-  let hunks = parse_all_err(P_HUNKS, &stdout)?;
+  let hunks = parse_patch_hunks(
+    &options.repo_path,
+    &stdout,
+    &options.patch,
+    &options.diff_options,
+  )?;
   let hunk_lines = flatten_hunks(hunks.clone());
 
   Ok((hunks, hunk_lines))
@@ -35,16 +47,122 @@ type HunkLinesSplit = (Vec<Hunk>, Vec<HunkLine>, Vec<HunkLine>);
 pub fn load_hunks_split(options: &ReqHunkOptions) -> R<HunkLinesSplit> {
   let GitOut { stdout, .. } = run_git::run_git_err(RunGitOptions {
     repo_path: &options.repo_path,
-    args: load_hunks_args(&options.commit, &options.patch),
+    args: load_hunks_args(&options.commit, &options.patch, &options.diff_options),
   })?;
 
-  let hunks = parse_all_err(P_HUNKS, &stdout)?;
+  let hunks = parse_patch_hunks(
+    &options.repo_path,
+    &stdout,
+    &options.patch,
+    &options.diff_options,
+  )?;
   let (hunk_lines_left, hunk_lines_right) = flatten_hunks_split(&hunks);
 
   Ok((hunks, hunk_lines_left, hunk_lines_right))
 }
 
-pub fn load_hunks_args(commit: &Commit, patch: &Patch) -> Vec<String> {
+// Git outputs nothing if all changes are ignored, e.g. with --ignore-all-space.
+pub fn parse_hunks(out: &str) -> R<Vec<Hunk>> {
+  if out.trim().is_empty() {
+    return Ok(Vec::new());
+  }
+
+  parse_all_err(P_HUNKS, out)
+}
+
+fn parse_patch_hunks(
+  repo_path: &str,
+  out: &str,
+  patch: &Patch,
+  diff_options: &DiffOptions,
+) -> R<Vec<Hunk>> {
+  let mut hunks = parse_hunks(out)?;
+
+  if let Some(rules) = WhitespaceRules::from_config(repo_path) {
+    for hunk in &mut hunks {
+      add_line_warnings(&mut hunk.lines, &rules);
+    }
+  }
+
+  if diff_options.detect_moved {
+    detect_moved_lines_in_file(&patch.old_file, &patch.new_file, &mut hunks);
+  }
+
+  Ok(hunks)
+}
+
+#[derive(Debug, Deserialize, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct ReqCommitHunksOptions {
+  pub repo_path: String,
+  pub commit: Commit,
+  pub patches: Vec<Patch>,
+  #[serde(default)]
+  pub diff_options: DiffOptions,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct PatchHunks {
+  pub patch: Patch,
+  pub hunks: Vec<Hunk>,
+  pub hunk_lines: Vec<HunkLine>,
+}
+
+// Hunks for several patches of a commit at once, so moved code can be found across files.
+pub fn load_commit_hunks(options: &ReqCommitHunksOptions) -> R<Vec<PatchHunks>> {
+  let ReqCommitHunksOptions {
+    repo_path,
+    commit,
+    patches,
+    diff_options,
+  } = options;
+
+  let mut files = Vec::<FileHunks>::new();
+
+  for patch in patches {
+    let GitOut { stdout, .. } = run_git::run_git_err(RunGitOptions {
+      repo_path,
+      args: load_hunks_args(commit, patch, diff_options),
+    })?;
+
+    files.push(FileHunks {
+      old_file: patch.old_file.clone(),
+      new_file: patch.new_file.clone(),
+      hunks: parse_hunks(&stdout)?,
+    });
+  }
+
+  if let Some(rules) = WhitespaceRules::from_config(repo_path) {
+    for hunk in files.iter_mut().flat_map(|f| f.hunks.iter_mut()) {
+      add_line_warnings(&mut hunk.lines, &rules);
+    }
+  }
+
+  if diff_options.detect_moved {
+    detect_moved_lines(&mut files);
+  }
+
+  Ok(
+    patches
+      .iter()
+      .zip(files)
+      .map(|(patch, file)| PatchHunks {
+        patch: patch.clone(),
+        hunk_lines: flatten_hunks(file.hunks.clone()),
+        hunks: file.hunks,
+      })
+      .collect(),
+  )
+}
+
+pub fn load_hunks_args(
+  commit: &Commit,
+  patch: &Patch,
+  diff_options: &DiffOptions,
+) -> Vec<String> {
   let diff = "diff".to_string();
   let no_colour = f!("--no-color");
   let dashes = "--".to_string();
@@ -71,17 +189,106 @@ pub fn load_hunks_args(commit: &Commit, patch: &Patch) -> Vec<String> {
     args.extend_from_slice(&[diff, no_colour, f!("{}..{}", COMMIT_0_ID, id)]);
   }
 
+  args.extend(diff_options.git_args());
   args.push(dashes);
-  args.push(patch.old_file.clone());
+  args.extend(patch_paths(patch));
+
+  args
+}
+
+fn patch_paths(patch: &Patch) -> Vec<String> {
+  let mut paths = vec![patch.old_file.clone()];
 
   if patch.patch_type == PatchType::R {
-    args.push(patch.new_file.clone());
+    paths.push(patch.new_file.clone());
   }
 
+  paths
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct ReqHunksBetweenOptions {
+  pub repo_path: String,
+  // Any revision: commit id, branch, tag etc.
+  pub from: String,
+  pub to: String,
+  // Diff "to" against the merge base of "from" and "to".
+  pub three_dot: bool,
+  // From load_patches_between.
+  pub patch: Patch,
+  #[serde(default)]
+  pub diff_options: DiffOptions,
+}
+
+pub fn load_hunks_between(
+  options: &ReqHunksBetweenOptions,
+) -> R<(Vec<Hunk>, Vec<HunkLine>)> {
+  let GitOut { stdout, .. } = run_git::run_git_err(RunGitOptions {
+    repo_path: &options.repo_path,
+    args: load_hunks_between_args(options),
+  })?;
+
+  let hunks = parse_patch_hunks(
+    &options.repo_path,
+    &stdout,
+    &options.patch,
+    &options.diff_options,
+  )?;
+  let hunk_lines = flatten_hunks(hunks.clone());
+
+  Ok((hunks, hunk_lines))
+}
+
+pub fn load_hunks_between_split(options: &ReqHunksBetweenOptions) -> R<HunkLinesSplit> {
+  let GitOut { stdout, .. } = run_git::run_git_err(RunGitOptions {
+    repo_path: &options.repo_path,
+    args: load_hunks_between_args(options),
+  })?;
+
+  let hunks = parse_patch_hunks(
+    &options.repo_path,
+    &stdout,
+    &options.patch,
+    &options.diff_options,
+  )?;
+  let (hunk_lines_left, hunk_lines_right) = flatten_hunks_split(&hunks);
+
+  Ok((hunks, hunk_lines_left, hunk_lines_right))
+}
+
+fn load_hunks_between_args(options: &ReqHunksBetweenOptions) -> Vec<String> {
+  let ReqHunksBetweenOptions {
+    from,
+    to,
+    three_dot,
+    patch,
+    diff_options,
+    ..
+  } = options;
+
+  let mut args = vec![
+    f!("diff"),
+    f!("--no-color"),
+    revision_range(from, to, *three_dot),
+  ];
+  args.extend(diff_options.git_args());
+  args.push(f!("--"));
+  args.extend(patch_paths(patch));
+
   args
 }
 
-fn flatten_hunks(hunks: Vec<Hunk>) -> Vec<HunkLine> {
+pub fn revision_range(from: &str, to: &str, three_dot: bool) -> String {
+  if three_dot {
+    f!("{}...{}", from, to)
+  } else {
+    f!("{}..{}", from, to)
+  }
+}
+
+pub fn flatten_hunks(hunks: Vec<Hunk>) -> Vec<HunkLine> {
   let mut lines: Vec<HunkLine> = Vec::new();
 
   if hunks.is_empty() {
@@ -1,6 +1,14 @@
+pub mod code_tokens;
+pub mod diff_options;
+pub mod expand_context;
 pub mod html_code;
 pub mod html_code_split;
 mod hunk_line_parsers;
 pub mod hunk_parsers;
 pub mod images;
+pub mod inline_changes;
+pub mod line_history;
+pub mod line_warnings;
 pub mod load_hunks;
+pub mod moved_lines;
+pub mod side_text;
@@ -0,0 +1,243 @@
+use std::collections::HashMap;
+
+use crate::git::git_types::{Hunk, HunkLine, HunkLineStatus, MovedLine};
+
+// Same as git's --color-moved: blocks with fewer alphanumeric chars aren't counted.
+const MIN_ALNUM_CHARS: usize = 20;
+// Lines like "}" match everywhere. Don't try too many candidates per line.
+const MAX_CANDIDATES: usize = 100;
+
+pub struct FileHunks {
+  pub old_file: String,
+  pub new_file: String,
+  pub hunks: Vec<Hunk>,
+}
+
+#[derive(Clone, Copy)]
+struct LineRef {
+  file: usize,
+  hunk: usize,
+  line: usize,
+}
+
+pub fn detect_moved_lines_in_file(old_file: &str, new_file: &str, hunks: &mut Vec<Hunk>) {
+  let mut files = [FileHunks {
+    old_file: old_file.to_string(),
+    new_file: new_file.to_string(),
+    hunks: std::mem::take(hunks),
+  }];
+
+  detect_moved_lines(&mut files);
+
+  *hunks = std::mem::take(&mut files[0].hunks);
+}
+
+/*
+Finds blocks of removed lines that were added unchanged somewhere else, possibly in
+another file. Both sides of a block get the same group id and point at each other, so
+they can be coloured like git's --color-moved=zebra.
+ */
+pub fn detect_moved_lines(files: &mut [FileHunks]) {
+  let removed = collect_lines(files, HunkLineStatus::Removed);
+  let added = collect_lines(files, HunkLineStatus::Added);
+
+  let mut added_by_text: HashMap<&str, Vec<usize>> = HashMap::new();
+  for (i, r) in added.iter().enumerate() {
+    added_by_text
+      .entry(&get(files, r).text)
+      .or_default()
+      .push(i);
+  }
+
+  let mut added_used = vec![false; added.len()];
+  let mut blocks: Vec<(usize, usize, usize)> = Vec::new();
+
+  let mut r = 0;
+  while r < removed.len() {
+    let text = &get(files, &removed[r]).text;
+
+    let candidates = if count_alnum(text) > 0 {
+      added_by_text.get(text.as_str())
+    } else {
+      None
+    };
+
+    let mut best: Option<(usize, usize)> = None;
+
+    for &a in candidates.into_iter().flatten().take(MAX_CANDIDATES) {
+      let len = match_length(files, &removed, &added, r, a, &added_used);
+
+      if best.map(|(_, best_len)| len > best_len).unwrap_or(true) {
+        best = Some((a, len));
+      }
+    }
+
+    if let Some((a, len)) = best {
+      let alnum: usize = (r..r + len)
+        .map(|i| count_alnum(&get(files, &removed[i]).text))
+        .sum();
+
+      if len > 0 && alnum >= MIN_ALNUM_CHARS {
+        for i in 0..len {
+          added_used[a + i] = true;
+        }
+        blocks.push((r, a, len));
+        r += len;
+        continue;
+      }
+    }
+
+    r += 1;
+  }
+
+  for (group, (r, a, len)) in blocks.into_iter().enumerate() {
+    for i in 0..len {
+      let removed_ref = removed[r + i];
+      let added_ref = added[a + i];
+
+      let to = MovedLine {
+        group: group as u32,
+        file: files[added_ref.file].new_file.clone(),
+        line_num: get(files, &added_ref).new_num.unwrap_or(0),
+      };
+      let from = MovedLine {
+        group: group as u32,
+        file: files[removed_ref.file].old_file.clone(),
+        line_num: get(files, &removed_ref).old_num.unwrap_or(0),
+      };
+
+      get_mut(files, &removed_ref).moved = Some(to);
+      get_mut(files, &added_ref).moved = Some(from);
+    }
+  }
+}
+
+// How many lines match starting from removed[r] and added[a]. Lines in a block need to be
+// next to each other in the same file on both sides.
+fn match_length(
+  files: &[FileHunks],
+  removed: &[LineRef],
+  added: &[LineRef],
+  r: usize,
+  a: usize,
+  added_used: &[bool],
+) -> usize {
+  let mut len = 0;
+
+  while r + len < removed.len() && a + len < added.len() && !added_used[a + len] {
+    let removed_line = get(files, &removed[r + len]);
+    let added_line = get(files, &added[a + len]);
+
+    if removed_line.text != added_line.text {
+      break;
+    }
+
+    if len > 0 {
+      let prev_removed = get(files, &removed[r + len - 1]);
+      let prev_added = get(files, &added[a + len - 1]);
+
+      if removed[r + len].file != removed[r + len - 1].file
+        || added[a + len].file != added[a + len - 1].file
+        || !follows(prev_removed.old_num, removed_line.old_num)
+        || !follows(prev_added.new_num, added_line.new_num)
+      {
+        break;
+      }
+    }
+
+    len += 1;
+  }
+
+  len
+}
+
+fn follows(prev: Option<i32>, next: Option<i32>) -> bool {
+  matches!((prev, next), (Some(p), Some(n)) if n == p + 1)
+}
+
+fn collect_lines(files: &[FileHunks], status: HunkLineStatus) -> Vec<LineRef> {
+  let mut refs = Vec::new();
+
+  for (file, f) in files.iter().enumerate() {
+    for (hunk, h) in f.hunks.iter().enumerate() {
+      for (line, l) in h.lines.iter().enumerate() {
+        if l.status == status {
+          refs.push(LineRef { file, hunk, line });
+        }
+      }
+    }
+  }
+
+  refs
+}
+
+fn get<'a>(files: &'a [FileHunks], r: &LineRef) -> &'a HunkLine {
+  &files[r.file].hunks[r.hunk].lines[r.line]
+}
+
+fn get_mut<'a>(files: &'a mut [FileHunks], r: &LineRef) -> &'a mut HunkLine {
+  &mut files[r.file].hunks[r.hunk].lines[r.line]
+}
+
+fn count_alnum(text: &str) -> usize {
+  text.chars().filter(|c| c.is_alphanumeric()).count()
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::git::queries::hunks::diff_options::DiffOptions;
+  use crate::git::queries::hunks::moved_lines::{detect_moved_lines, FileHunks};
+  use crate::git::queries::wip::create_hunks::convert_lines_to_hunks;
+  use crate::git::queries::wip::wip_diff::calc_hunk_line_from_text;
+
+  fn make_file(name: &str, old: &str, new: &str) -> FileHunks {
+    let options = DiffOptions::default();
+    let lines = calc_hunk_line_from_text(old, new, &options);
+
+    FileHunks {
+      old_file: name.to_string(),
+      new_file: name.to_string(),
+      hunks: convert_lines_to_hunks(lines, &options).0,
+    }
+  }
+
+  #[test]
+  fn test_detect_moved_lines() {
+    let block = "fn moved_function() {\n  do_something_useful();\n}\n";
+
+    let mut files = [
+      make_file("a.rs", &format!("fn a() {{}}\n{}", block), "fn a() {}\n"),
+      make_file("b.rs", "fn b() {}\n", &format!("fn b() {{}}\n{}", block)),
+    ];
+
+    detect_moved_lines(&mut files);
+
+    let removed = &files[0].hunks[0].lines;
+    let added = &files[1].hunks[0].lines;
+
+    let moved_removed: Vec<_> = removed.iter().filter_map(|l| l.moved.as_ref()).collect();
+    let moved_added: Vec<_> = added.iter().filter_map(|l| l.moved.as_ref()).collect();
+
+    assert_eq!(moved_removed.len(), 3);
+    assert_eq!(moved_added.len(), 3);
+    assert_eq!(moved_removed[0].file, "b.rs");
+    assert_eq!(moved_removed[0].line_num, 2);
+    assert_eq!(moved_added[0].file, "a.rs");
+    assert_eq!(moved_added[0].line_num, 2);
+  }
+
+  #[test]
+  fn test_short_blocks_ignored() {
+    let mut files = [
+      make_file("a.rs", "x\n}\n", "x\n"),
+      make_file("b.rs", "y\n", "y\n}\n"),
+    ];
+
+    detect_moved_lines(&mut files);
+
+    assert!(files
+      .iter()
+      .flat_map(|f| f.hunks.iter().flat_map(|h| h.lines.iter()))
+      .all(|l| l.moved.is_none()));
+  }
+}
@@ -0,0 +1,83 @@
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::f;
+use crate::git::git_types::{Commit, Patch};
+use crate::git::queries::COMMIT_0_ID;
+use crate::git::run_git::{run_git_err, RunGitOptions};
+use crate::server::request_util::{ES, R};
+
+// A file's text from either side of a diff, shared by context expansion and rendering.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, TS)]
+#[ts(export)]
+pub enum ContextSide {
+  Old,
+  New,
+}
+
+pub fn load_commit_side_text(
+  repo_path: &str,
+  commit: &Commit,
+  patch: &Patch,
+  side: ContextSide,
+) -> R<String> {
+  match side {
+    ContextSide::Old => load_file_at_revision(
+      repo_path,
+      &old_revision(repo_path, commit)?,
+      &patch.old_file,
+    ),
+    ContextSide::New => {
+      load_file_at_revision(repo_path, &new_revision(commit), &patch.new_file)
+    }
+  }
+}
+
+pub fn load_file_at_revision(repo_path: &str, revision: &str, path: &str) -> R<String> {
+  let out = run_git_err(RunGitOptions {
+    repo_path,
+    args: [f!("show"), f!("{}:{}", revision, path)],
+  })?;
+
+  if out.stdout.is_empty() && !out.stderr.is_empty() {
+    return Err(ES::from(out.stderr.trim()));
+  }
+
+  Ok(out.stdout)
+}
+
+pub fn merge_base(repo_path: &str, a: &str, b: &str) -> R<String> {
+  let out = run_git_err(RunGitOptions {
+    repo_path,
+    args: [f!("merge-base"), a.to_string(), b.to_string()],
+  })?;
+
+  if out.stdout.trim().is_empty() && !out.stderr.is_empty() {
+    return Err(ES::from(out.stderr.trim()));
+  }
+
+  Ok(out.stdout.trim().to_string())
+}
+
+// Matches the revisions diffed in load_hunks_args.
+fn old_revision(repo_path: &str, commit: &Commit) -> R<String> {
+  let Commit {
+    parent_ids,
+    is_merge,
+    ..
+  } = commit;
+
+  if *is_merge {
+    return merge_base(repo_path, &parent_ids[0], &parent_ids[1]);
+  }
+
+  Ok(parent_ids.first().cloned().unwrap_or(f!("{}", COMMIT_0_ID)))
+}
+
+fn new_revision(commit: &Commit) -> String {
+  if commit.is_merge {
+    commit.parent_ids[1].clone()
+  } else {
+    commit.id.clone()
+  }
+}
@@ -1,6 +1,7 @@
 use crate::git::git_types::RefInfo;
 use crate::git::queries::refs::P_OPTIONAL_REFS;
 
+pub(crate) mod blame;
 pub(crate) mod commits;
 mod commits_parsers;
 mod commits_test;
@@ -8,6 +9,7 @@ mod commits_test;
 pub(crate) mod commit_calcs;
 mod commit_filters;
 pub(crate) mod config;
+pub(crate) mod file_history;
 pub(crate) mod hunks;
 pub(crate) mod patches;
 pub(crate) mod refs;
@@ -16,7 +18,8 @@ pub(crate) mod scan_workspace;
 pub(crate) mod search;
 pub(crate) mod stashes;
 mod stashes_test;
-mod syntax_colouring;
+pub(crate) mod syntax_colouring;
+pub(crate) mod tags;
 pub(crate) mod unpushed_commits;
 pub(crate) mod wip;
 pub mod workspace;
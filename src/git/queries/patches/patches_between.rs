@@ -0,0 +1,195 @@
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::f;
+use crate::git::git_types::Patch;
+use crate::git::queries::hunks::load_hunks::revision_range;
+use crate::git::queries::patches::patch_parsers::{map_data_to_patch, P_PATCHES};
+use crate::git::run_git::{run_git_err, RunGitOptions};
+use crate::parser::parse_all_err;
+use crate::server::request_util::{ES, R};
+
+#[derive(Debug, Deserialize, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct ReqPatchesBetweenOptions {
+  pub repo_path: String,
+  // Any revision: commit id, branch, tag etc.
+  pub from: String,
+  pub to: String,
+  // Compare "to" with the merge base of "from" and "to", e.g. what a feature branch
+  // adds to main.
+  pub three_dot: bool,
+  // Only include these paths if not empty.
+  pub paths: Vec<String>,
+}
+
+// The patches' commit_id is set to the commit "to" resolves to.
+pub fn load_patches_between(options: &ReqPatchesBetweenOptions) -> R<Vec<Patch>> {
+  let ReqPatchesBetweenOptions {
+    repo_path,
+    from,
+    to,
+    three_dot,
+    paths,
+  } = options;
+
+  let to_id = resolve_commit_id(repo_path, to)?;
+
+  let out = run_git_err(RunGitOptions {
+    repo_path,
+    args: patches_between_args(from, &to_id, *three_dot, paths),
+  })?
+  .stdout;
+
+  parse_patches_between(&out, &to_id)
+}
+
+// E.g. "main" -> the id of the commit it points to. Tags are peeled to their commit.
+pub fn resolve_commit_id(repo_path: &str, revision: &str) -> R<String> {
+  let out = run_git_err(RunGitOptions {
+    repo_path,
+    args: [
+      "rev-parse",
+      "--verify",
+      "--quiet",
+      &f!("{}^{{commit}}", revision),
+    ],
+  })?;
+
+  let id = out.stdout.trim();
+
+  if id.is_empty() {
+    return Err(ES::from(&f!("Couldn't find a commit for \"{}\"", revision)));
+  }
+
+  Ok(id.to_string())
+}
+
+fn patches_between_args(
+  from: &str,
+  to: &str,
+  three_dot: bool,
+  paths: &[String],
+) -> Vec<String> {
+  let mut args = vec![
+    f!("diff"),
+    f!("--name-status"),
+    f!("--no-color"),
+    f!("-z"),
+    revision_range(from, to, three_dot),
+  ];
+
+  if !paths.is_empty() {
+    args.push(f!("--"));
+    args.extend(paths.iter().cloned());
+  }
+
+  args
+}
+
+fn parse_patches_between(out: &str, to_id: &str) -> R<Vec<Patch>> {
+  let patch_data = parse_all_err(P_PATCHES, out)?;
+
+  Ok(
+    patch_data
+      .into_iter()
+      .map(|data| map_data_to_patch(data, to_id.to_string()))
+      .collect(),
+  )
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::git::git_types::PatchType;
+  use crate::git::queries::hunks::load_hunks::{
+    load_hunks_between, ReqHunksBetweenOptions,
+  };
+  use crate::git::queries::patches::patches_between::{
+    load_patches_between, parse_patches_between, patches_between_args,
+    ReqPatchesBetweenOptions,
+  };
+  use crate::git::test_repo::TestRepo;
+
+  #[test]
+  fn test_patches_between_args() {
+    assert_eq!(
+      patches_between_args("main", "abc", true, &["src".to_string()]),
+      [
+        "diff",
+        "--name-status",
+        "--no-color",
+        "-z",
+        "main...abc",
+        "--",
+        "src"
+      ]
+    );
+    assert_eq!(
+      patches_between_args("main", "abc", false, &[])
+        .last()
+        .unwrap(),
+      "main..abc"
+    );
+  }
+
+  #[test]
+  fn test_parse_patches_between() {
+    let patches =
+      parse_patches_between("M\0a.rs\0R100\0b.rs\0c.rs\0D\0d.rs\0", "abc").unwrap();
+
+    assert_eq!(patches.len(), 3);
+    assert_eq!(patches[0].patch_type, PatchType::M);
+    assert_eq!(patches[1].old_file, "b.rs");
+    assert_eq!(patches[1].new_file, "c.rs");
+    assert_eq!(patches[2].patch_type, PatchType::D);
+    assert!(patches.iter().all(|p| p.commit_id == "abc"));
+  }
+
+  #[test]
+  fn test_load_patches_and_hunks_between() {
+    let repo = TestRepo::new("patches_between");
+    repo.write("a.txt", "1\n2\n3\n");
+    repo.commit("first");
+    repo.git(&["tag", "-a", "v1", "-m", "v1"]);
+    repo.git(&["checkout", "-q", "-b", "feature"]);
+    repo.write("a.txt", "1\nX\n3\n");
+    repo.write("b.txt", "b\n");
+    let feature_id = repo.commit("second");
+
+    let repo_path = repo.path_str();
+    let patches = load_patches_between(&ReqPatchesBetweenOptions {
+      repo_path: repo_path.clone(),
+      from: "v1".to_string(),
+      to: "feature".to_string(),
+      three_dot: false,
+      paths: Vec::new(),
+    })
+    .unwrap();
+
+    assert_eq!(patches.len(), 2);
+    assert_eq!(patches[0].commit_id, feature_id);
+    assert_eq!(patches[1].patch_type, PatchType::A);
+
+    let (hunks, _) = load_hunks_between(&ReqHunksBetweenOptions {
+      repo_path: repo_path.clone(),
+      from: "v1".to_string(),
+      to: "feature".to_string(),
+      three_dot: true,
+      patch: patches[0].clone(),
+      diff_options: Default::default(),
+    })
+    .unwrap();
+
+    assert_eq!(hunks.len(), 1);
+
+    assert!(load_patches_between(&ReqPatchesBetweenOptions {
+      repo_path,
+      from: "v1".to_string(),
+      to: "missing".to_string(),
+      three_dot: false,
+      paths: Vec::new(),
+    })
+    .is_err());
+  }
+}
@@ -1,11 +1,10 @@
 use std::time::Duration;
 
 use crate::git::git_types::{Commit, Hunk, HunkLine, HunkLineStatus, Patch};
-use crate::git::queries::hunks::hunk_parsers::P_HUNKS;
-use crate::git::queries::hunks::load_hunks::load_hunks_args;
+use crate::git::queries::hunks::diff_options::DiffOptions;
+use crate::git::queries::hunks::load_hunks::{load_hunks_args, parse_hunks};
 use crate::git::run_git::{run_git_err, RunGitOptions};
 use crate::global;
-use crate::parser::parse_all_err;
 use crate::server::request_util::R;
 use crate::util::global::Global;
 use crate::util::short_cache::ShortCache;
@@ -21,8 +20,9 @@ pub fn get_matching_hunk_lines(
   commit: &Commit,
   patch: &Patch,
   search_text: &str,
+  diff_options: &DiffOptions,
 ) -> R<Vec<HunkLine>> {
-  let cache_id = format!("{}{}", commit.id, patch.id);
+  let cache_id = format!("{}{}{:?}", commit.id, patch.id, diff_options);
 
   if let Some(hunks) = get_hunks_from_cache(&cache_id) {
     return Ok(get_matching_lines_in_hunks(hunks, search_text));
@@ -30,10 +30,10 @@ pub fn get_matching_hunk_lines(
 
   let out = run_git_err(RunGitOptions {
     repo_path,
-    args: load_hunks_args(commit, patch),
+    args: load_hunks_args(commit, patch, diff_options),
   })?;
 
-  let hunks = parse_all_err(P_HUNKS, &out.stdout)?;
+  let hunks = parse_hunks(&out.stdout)?;
   store_hunk_in_cache(&cache_id, hunks.clone());
 
   let hunk_lines = get_matching_lines_in_hunks(hunks, search_text);
@@ -42,6 +42,7 @@ fn search_cancelled(search_id: u32) -> bool {
 #[cfg(test)]
 mod tests {
   use crate::git::git_types::Patch;
+  use crate::git::queries::hunks::diff_options::DiffOptions;
   use crate::git::queries::patches::patch_parsers::P_MANY_PATCHES_WITH_COMMIT_IDS;
   use std::time::{Duration, Instant};
   use std::{assert_eq, println, thread};
@@ -80,6 +81,7 @@ mod tests {
         search_text: "this".to_string(),
         repo_path: ".".to_string(),
         start_commit_index: 0,
+        diff_options: DiffOptions::default(),
       })
     });
 
@@ -91,6 +93,7 @@ mod tests {
         search_text: "this".to_string(),
         repo_path: ".".to_string(),
         start_commit_index: 0,
+        diff_options: DiffOptions::default(),
       })
     });
 
@@ -102,6 +105,7 @@ mod tests {
         search_text: "this".to_string(),
         repo_path: ".".to_string(),
         start_commit_index: 0,
+        diff_options: DiffOptions::default(),
       })
     });
 
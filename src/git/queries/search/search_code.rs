@@ -1,6 +1,7 @@
 use crate::dprintln;
 use crate::git::git_settings::GIT_PATH;
 use crate::git::git_types::{HunkLine, Patch};
+use crate::git::queries::hunks::diff_options::DiffOptions;
 use crate::git::queries::patches::patch_parsers::P_MANY_PATCHES_WITH_COMMIT_IDS;
 use crate::git::queries::search::matching_hunk_lines::get_matching_hunk_lines;
 use crate::git::queries::search::search_cancelled;
@@ -21,6 +22,8 @@ pub struct CodeSearchOpts {
   pub search_text: String,
   pub num_results: usize,
   pub start_commit_index: usize,
+  #[serde(default)]
+  pub diff_options: DiffOptions,
 }
 
 #[derive(Debug, Clone, Serialize, Eq, PartialEq, TS)]
@@ -42,6 +45,7 @@ pub fn search_commits_for_code(
   let CodeSearchOpts {
     repo_path,
     search_text,
+    diff_options,
     ..
   } = options;
 
@@ -57,8 +61,14 @@ pub fn search_commits_for_code(
           .into_iter()
           .flat_map(|patch| {
             Some(FileMatch {
-              lines: get_matching_hunk_lines(repo_path, commit, &patch, search_text)
-                .ok()?,
+              lines: get_matching_hunk_lines(
+                repo_path,
+                commit,
+                &patch,
+                search_text,
+                diff_options,
+              )
+              .ok()?,
               patch,
             })
           })
@@ -84,6 +94,7 @@ pub fn search_code_command(options: &CodeSearchOpts, search_id: u32) -> Option<S
     search_text,
     num_results,
     start_commit_index,
+    ..
   } = options;
 
   let mut cmd = Command::new(GIT_PATH.as_path())
@@ -1,9 +1,31 @@
+mod definitions;
+pub mod token_classes;
+
+use crate::git::git_types::{HunkLine, HunkLineStatus};
+use crate::git::queries::syntax_colouring::definitions::{
+  load_syntax_set, load_theme_set,
+};
+use crate::git::queries::syntax_colouring::token_classes::{
+  generate_stylesheet, TokenClasses, CLASS_PREFIX,
+};
+use crate::git::run_git::{run_git_err, RunGitOptions};
+use crate::git::store::LINGUIST_LANGUAGES;
+use crate::server::git_request::ReqOptions;
+use crate::server::request_util::{ES, R};
 use crate::util::global::Glo;
-use crate::{dprintln, f, glo};
+use crate::{f, glo};
 use serde::{Deserialize, Serialize};
-use syntect::easy::HighlightLines;
-use syntect::highlighting::{Color, Style, ThemeSet};
-use syntect::parsing::SyntaxSet;
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::path::Path;
+use std::rc::Rc;
+use syntect::easy::ScopeRangeIterator;
+use syntect::highlighting::{
+  Color, HighlightIterator, HighlightState, Highlighter, Style, Theme, ThemeSet,
+};
+use syntect::parsing::{ParseState, Scope, ScopeStack, SyntaxReference, SyntaxSet};
 use ts_rs::TS;
 
 pub static COLOURING: Glo<Colouring> = glo!(Colouring::new());
@@ -12,9 +34,16 @@ pub struct Colouring {
   pub syntax_set: SyntaxSet,
   pub theme_set: ThemeSet,
   pub theme: ThemeColour,
+  // Names in theme_set to use for each ThemeColour.
+  pub light_theme: String,
+  pub dark_theme: String,
+  pub token_classes: TokenClasses,
 }
 
-#[derive(Debug, Eq, PartialEq, Copy, Clone, Deserialize, Serialize, TS)]
+const DEFAULT_LIGHT_THEME: &str = "base16-ocean.light";
+const DEFAULT_DARK_THEME: &str = "base16-ocean.dark";
+
+#[derive(Debug, Eq, PartialEq, Hash, Copy, Clone, Deserialize, Serialize, TS)]
 #[serde(rename_all = "camelCase")]
 #[ts(export)]
 pub enum ThemeColour {
@@ -22,12 +51,35 @@ pub enum ThemeColour {
   Dark,
 }
 
+// How highlighted HTML is coloured.
+#[derive(Debug, Default, Eq, PartialEq, Copy, Clone, Deserialize, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub enum ColourOutput {
+  // Inline style with just the hue of the theme colour.
+  #[default]
+  Inline,
+  // Classes from token_classes. Use with get_syntax_stylesheet.
+  Classes,
+}
+
+// A piece of a highlighted line.
+pub struct Token<'b> {
+  pub style: Style,
+  // Innermost last.
+  pub scopes: Vec<Scope>,
+  pub text: &'b str,
+}
+
 impl Colouring {
   pub fn new() -> Self {
     Self {
-      syntax_set: SyntaxSet::load_defaults_newlines(),
-      theme_set: ThemeSet::load_defaults(),
+      syntax_set: load_syntax_set(),
+      theme_set: load_theme_set(),
       theme: ThemeColour::Light,
+      light_theme: DEFAULT_LIGHT_THEME.to_string(),
+      dark_theme: DEFAULT_DARK_THEME.to_string(),
+      token_classes: TokenClasses::new(),
     }
   }
 
@@ -35,48 +87,94 @@ impl Colouring {
     self.theme = *theme;
   }
 
-  pub fn get_colour_line(
+  /*
+  The syntax comes from the linguist-language git attribute if set, then the file
+  extension or name, then the first line (e.g. a shebang).
+   */
+  pub fn get_colour_line_for_file(
     &mut self,
     theme: &ThemeColour,
-    file_extension: &str,
+    repo_path: &str,
+    file_path: &str,
+    first_line: Option<&str>,
   ) -> ColourLine {
     self.set_theme(theme);
-    let h = self.get_highlighter(file_extension);
+    let syntax = self.find_syntax_for_file(repo_path, file_path, first_line);
 
-    dprintln!(
-      "file_ext: {}, highlighter loaded: {}",
-      file_extension,
-      h.is_some()
-    );
+    self.make_colour_line(syntax)
+  }
+
+  fn find_syntax_for_file(
+    &self,
+    repo_path: &str,
+    file_path: &str,
+    first_line: Option<&str>,
+  ) -> Option<&SyntaxReference> {
+    get_linguist_language(repo_path, file_path)
+      .and_then(|language| self.syntax_set.find_syntax_by_token(&language))
+      .or_else(|| {
+        let path = Path::new(file_path);
+        let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+        let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+
+        self
+          .find_syntax(extension)
+          .or_else(|| self.find_syntax(name))
+      })
+      .or_else(|| self.syntax_set.find_syntax_by_first_line(first_line?))
+  }
+
+  fn make_colour_line<'a>(
+    &'a self,
+    syntax: Option<&'a SyntaxReference>,
+  ) -> ColourLine<'a> {
+    let highlighter = Highlighter::new(self.get_theme());
 
     ColourLine {
       colouring: self,
-      highlight: h,
-      // extension: file_extension.to_string(),
+      fragment: syntax.map(|s| LineState::new(s, &highlighter)),
+      highlighter,
+      syntax,
+      old_file: None,
+      new_file: None,
+      output: ColourOutput::Inline,
     }
   }
 
-  // HighlightLines isn't thread safe, so can't be stored in a global.
-  pub fn get_highlighter(&self, file_extension: &str) -> Option<HighlightLines> {
+  fn find_syntax(&self, file_extension: &str) -> Option<&SyntaxReference> {
     let ext = match file_extension {
-      "ts" => "js",
-      "tsx" => "js",
       "iml" => "xml",
+      "" => return None,
       _ => file_extension,
     };
 
-    let syntax = self.syntax_set.find_syntax_by_extension(ext)?;
+    self.syntax_set.find_syntax_by_extension(ext)
+  }
 
-    let theme_str = if self.theme == ThemeColour::Dark {
-      "base16-ocean.dark"
+  fn get_theme(&self) -> &Theme {
+    &self.theme_set.themes[self.get_theme_name()]
+  }
+
+  // Falls back to the default if the chosen theme is missing.
+  fn get_theme_name(&self) -> &str {
+    let (name, default) = if self.theme == ThemeColour::Dark {
+      (&self.dark_theme, DEFAULT_DARK_THEME)
     } else {
-      "base16-ocean.light"
+      (&self.light_theme, DEFAULT_LIGHT_THEME)
     };
 
-    Some(HighlightLines::new(
-      syntax,
-      &self.theme_set.themes[theme_str],
-    ))
+    if self.theme_set.themes.contains_key(name) {
+      name
+    } else {
+      default
+    }
+  }
+
+  // Styles for the classes in ColourOutput::Classes, from the current theme.
+  pub fn get_stylesheet(&mut self, theme: &ThemeColour) -> String {
+    self.set_theme(theme);
+
+    generate_stylesheet(self.get_theme())
   }
 
   pub fn _get_supported_things(self) -> (Vec<String>, Vec<String>) {
@@ -93,23 +191,145 @@ impl Colouring {
   }
 }
 
+// Returns None for "unspecified" etc.
+fn get_linguist_language(repo_path: &str, file_path: &str) -> Option<String> {
+  let cache_key = (repo_path.to_string(), file_path.to_string());
+
+  if let Some(language) = LINGUIST_LANGUAGES.get_by_key(&cache_key) {
+    return language;
+  }
+
+  let out = run_git_err(RunGitOptions {
+    repo_path,
+    args: ["check-attr", "linguist-language", "--", file_path],
+  })
+  .ok()?;
+
+  let language = parse_linguist_language(&out.stdout);
+
+  LINGUIST_LANGUAGES.insert(cache_key, language.clone());
+
+  language
+}
+
+// "src/a.x: linguist-language: TypeScript"
+fn parse_linguist_language(out: &str) -> Option<String> {
+  let value = out.trim().rsplit(": ").next()?;
+
+  match value {
+    "" | "unspecified" | "set" | "unset" => None,
+    _ => Some(value.to_string()),
+  }
+}
+
+pub fn clear_linguist_languages(repo_path: &str) {
+  if let Ok(mut languages) = LINGUIST_LANGUAGES.data.write() {
+    languages.retain(|(path, _), _| path != repo_path);
+  }
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct SyntaxTheme {
+  pub name: String,
+  // Has a dark background.
+  pub dark: bool,
+  pub selected: bool,
+}
+
+pub fn get_syntax_themes(_: &ReqOptions) -> R<Vec<SyntaxTheme>> {
+  let colouring = COLOURING.read()?;
+
+  let mut themes: Vec<SyntaxTheme> = colouring
+    .theme_set
+    .themes
+    .iter()
+    .map(|(name, theme)| {
+      let dark = theme
+        .settings
+        .background
+        .map(|c| (c.r as u32 + c.g as u32 + c.b as u32) < 384)
+        .unwrap_or(false);
+
+      SyntaxTheme {
+        name: name.clone(),
+        dark,
+        selected: *name == colouring.light_theme || *name == colouring.dark_theme,
+      }
+    })
+    .collect();
+
+  themes.sort_by(|a, b| a.name.cmp(&b.name));
+
+  Ok(themes)
+}
+
+#[derive(Debug, Deserialize, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct ReqSetSyntaxThemeOptions {
+  // Which of our themes to use it for.
+  pub theme: ThemeColour,
+  pub name: String,
+}
+
+pub fn set_syntax_theme(options: &ReqSetSyntaxThemeOptions) -> R<()> {
+  let mut colouring = COLOURING.write()?;
+
+  if !colouring.theme_set.themes.contains_key(&options.name) {
+    return Err(ES::from(&f!("Unknown theme \"{}\"", options.name)));
+  }
+
+  match options.theme {
+    ThemeColour::Light => colouring.light_theme = options.name.clone(),
+    ThemeColour::Dark => colouring.dark_theme = options.name.clone(),
+  }
+
+  Ok(())
+}
+
+#[derive(Debug, Deserialize, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct ReqSyntaxStylesheetOptions {
+  pub theme: ThemeColour,
+}
+
+pub fn get_syntax_stylesheet(options: &ReqSyntaxStylesheetOptions) -> R<String> {
+  Ok(COLOURING.write()?.get_stylesheet(&options.theme))
+}
+
 pub struct ColourLine<'a> {
   pub colouring: &'a Colouring,
-  pub highlight: Option<HighlightLines<'a>>,
-  // pub extension: String,
+  highlighter: Highlighter<'a>,
+  // State for lines coloured without the rest of the file.
+  fragment: Option<LineState>,
+  syntax: Option<&'a SyntaxReference>,
+  // Set with set_file_texts. Used instead of "fragment" for lines they contain.
+  old_file: Option<FileHighlighter<'a>>,
+  new_file: Option<FileHighlighter<'a>>,
+  pub output: ColourOutput,
 }
 
 impl<'a> ColourLine<'a> {
-  pub fn colour<'b>(&mut self, line: &'b str) -> Result<Vec<(Style, &'b str)>, String> {
-    if let Some(ref mut h) = self.highlight {
-      return h
-        .highlight_line(line, &self.colouring.syntax_set)
-        .map_err(|e| e.to_string());
+  pub fn colour<'b>(&mut self, line: &'b str) -> Result<Vec<Token<'b>>, String> {
+    if let Some(ref mut state) = self.fragment {
+      return highlight_line(state, &self.highlighter, &self.colouring.syntax_set, line);
     }
 
     Err(String::from("Highlighter isn't loaded for this file"))
   }
 
+  // With the prefix, e.g. "hl-keyword".
+  pub fn token_class(&self, token: &Token) -> String {
+    f!(
+      "{}{}",
+      CLASS_PREFIX,
+      self.colouring.token_classes.get_class(&token.scopes)
+    )
+  }
+
   // We are trying to highlight fragments of code the have missing context. Fake up the context.
   pub fn start_fragment(&mut self) {
     let _ = self.colour("{\n");
@@ -118,6 +338,292 @@ impl<'a> ColourLine<'a> {
   pub fn end_fragment(&mut self) {
     let _ = self.colour("}\n");
   }
+
+  /*
+  With the whole files, hunk lines are coloured with the state of the lines above them.
+  E.g. a block comment started before the hunk. Files too large to parse quickly are
+  left out, so their hunks are coloured as fragments.
+   */
+  pub fn set_file_texts(&mut self, old_text: Option<&'a str>, new_text: Option<&'a str>) {
+    if let Some(syntax) = self.syntax {
+      let colouring = self.colouring;
+      let file = |text: &'a str| FileHighlighter::new(colouring, syntax, text);
+
+      self.old_file = old_text.filter(|t| !too_large_to_parse(t)).map(file);
+      self.new_file = new_text.filter(|t| !too_large_to_parse(t)).map(file);
+    }
+  }
+
+  // Falls back to colouring the line as a fragment if we don't have the file text.
+  pub fn colour_hunk_line<'b>(
+    &mut self,
+    line: &HunkLine,
+    text: &'b str,
+  ) -> Result<Vec<Token<'b>>, String> {
+    use HunkLineStatus::*;
+
+    let (file, line_num) = match line.status {
+      Removed => (self.old_file.as_mut(), line.old_num),
+      Added | Unchanged => (self.new_file.as_mut(), line.new_num),
+      _ => (None, None),
+    };
+
+    if let (Some(file), Some(line_num)) = (file, line_num) {
+      if let Ok(parts) = file.colour(line_num as usize, text) {
+        return Ok(parts);
+      }
+    }
+
+    self.colour(text)
+  }
+}
+
+// Highlighting state is saved before every this many lines of a file.
+const CHECKPOINT_INTERVAL: usize = 50;
+const MAX_CACHED_FILES: usize = 20;
+// Above either of these we don't parse the file for context.
+const MAX_PARSE_BYTES: usize = 1_000_000;
+const MAX_PARSE_LINES: usize = 20_000;
+
+fn too_large_to_parse(text: &str) -> bool {
+  text.len() > MAX_PARSE_BYTES
+    || text.bytes().filter(|b| *b == b'\n').count() > MAX_PARSE_LINES
+}
+
+#[derive(Clone)]
+struct LineState {
+  parse: ParseState,
+  highlight: HighlightState,
+}
+
+impl LineState {
+  fn new(syntax: &SyntaxReference, highlighter: &Highlighter) -> Self {
+    Self {
+      parse: ParseState::new(syntax),
+      highlight: HighlightState::new(highlighter, ScopeStack::new()),
+    }
+  }
+}
+
+// The state before the line at "offset" bytes into the file.
+#[derive(Clone)]
+struct Checkpoint {
+  offset: usize,
+  state: LineState,
+}
+
+type Checkpoints = Rc<RefCell<Vec<Checkpoint>>>;
+
+thread_local! {
+  // Parse states aren't thread safe. Requests are handled on one thread, so this is still
+  // shared between them. Keyed by a hash of the file text, syntax and theme. Most recently
+  // used last. Checkpoints are added as later lines are parsed, e.g. for the next page.
+  static FILE_STATES: RefCell<Vec<(u64, Checkpoints)>> = const { RefCell::new(Vec::new()) };
+}
+
+/*
+Only parses as far as the lines asked for, saving the state every CHECKPOINT_INTERVAL
+lines. Lines are usually requested in order, so we just keep going from the last one.
+Otherwise we start from the nearest checkpoint.
+ */
+struct FileHighlighter<'a> {
+  colouring: &'a Colouring,
+  syntax: &'a SyntaxReference,
+  highlighter: Highlighter<'a>,
+  text: &'a str,
+  // Loaded on the first line coloured, as it needs a hash of the whole text.
+  checkpoints: Option<Checkpoints>,
+  state: LineState,
+  // Index of the line "state" is before, and where it starts in text.
+  next_line: usize,
+  next_offset: usize,
+}
+
+impl<'a> FileHighlighter<'a> {
+  fn new(colouring: &'a Colouring, syntax: &'a SyntaxReference, text: &'a str) -> Self {
+    let highlighter = Highlighter::new(colouring.get_theme());
+    let state = LineState::new(syntax, &highlighter);
+
+    Self {
+      colouring,
+      syntax,
+      highlighter,
+      text,
+      checkpoints: None,
+      state,
+      next_line: 0,
+      next_offset: 0,
+    }
+  }
+
+  fn load_checkpoints(&mut self) -> Checkpoints {
+    if let Some(checkpoints) = &self.checkpoints {
+      return checkpoints.clone();
+    }
+
+    let mut hasher = DefaultHasher::new();
+    self.text.hash(&mut hasher);
+    self.syntax.name.hash(&mut hasher);
+    self.colouring.theme.hash(&mut hasher);
+    self.colouring.get_theme_name().hash(&mut hasher);
+    let key = hasher.finish();
+
+    let checkpoints = get_cached_states(key).unwrap_or_else(|| {
+      let start = Checkpoint {
+        offset: 0,
+        state: LineState::new(self.syntax, &self.highlighter),
+      };
+      let checkpoints = Rc::new(RefCell::new(vec![start]));
+      cache_states(key, checkpoints.clone());
+      checkpoints
+    });
+
+    self.checkpoints = Some(checkpoints.clone());
+    checkpoints
+  }
+
+  // line_num is 1 based. The parts borrow from text, which should be the same as the line.
+  fn colour<'b>(
+    &mut self,
+    line_num: usize,
+    text: &'b str,
+  ) -> Result<Vec<Token<'b>>, String> {
+    let index = line_num
+      .checked_sub(1)
+      .ok_or_else(|| f!("Line {} isn't in the file", line_num))?;
+    let checkpoints = self.load_checkpoints();
+
+    {
+      let saved = checkpoints.borrow();
+      let nearest = (index / CHECKPOINT_INTERVAL).min(saved.len() - 1);
+      let nearest_line = nearest * CHECKPOINT_INTERVAL;
+
+      if index < self.next_line || nearest_line > self.next_line {
+        self.state = saved[nearest].state.clone();
+        self.next_line = nearest_line;
+        self.next_offset = saved[nearest].offset;
+      }
+    }
+
+    while self.next_line < index {
+      let line = self.file_line(line_num)?;
+      self.save_checkpoint(&checkpoints);
+      highlight_line(
+        &mut self.state,
+        &self.highlighter,
+        &self.colouring.syntax_set,
+        &line,
+      )?;
+      self.next_line += 1;
+      self.next_offset += line.len();
+    }
+
+    let len = self.file_line(line_num)?.len();
+    self.save_checkpoint(&checkpoints);
+    let parts = highlight_line(
+      &mut self.state,
+      &self.highlighter,
+      &self.colouring.syntax_set,
+      text,
+    )?;
+    self.next_line += 1;
+    self.next_offset += len;
+
+    Ok(parts)
+  }
+
+  // The line at next_offset, ending with a new line as syntect expects.
+  fn file_line(&self, line_num: usize) -> Result<Cow<'a, str>, String> {
+    let rest = &self.text[self.next_offset..];
+    if rest.is_empty() {
+      return Err(f!("Line {} isn't in the file", line_num));
+    }
+
+    Ok(match rest.find('\n') {
+      Some(end) => Cow::Borrowed(&rest[..=end]),
+      None => Cow::Owned(f!("{}\n", rest)),
+    })
+  }
+
+  // Only the first time this line is reached. Later ones start from a checkpoint.
+  fn save_checkpoint(&self, checkpoints: &Checkpoints) {
+    let mut saved = checkpoints.borrow_mut();
+
+    if self.next_line == saved.len() * CHECKPOINT_INTERVAL {
+      saved.push(Checkpoint {
+        offset: self.next_offset,
+        state: self.state.clone(),
+      });
+    }
+  }
+}
+
+fn highlight_line<'b>(
+  state: &mut LineState,
+  highlighter: &Highlighter,
+  syntax_set: &SyntaxSet,
+  line: &'b str,
+) -> Result<Vec<Token<'b>>, String> {
+  let ops = state
+    .parse
+    .parse_line(line, syntax_set)
+    .map_err(|e| e.to_string())?;
+
+  // The highlighter splits the line at the same places, so each part is in one of these.
+  let mut scopes = state.highlight.path.clone();
+  let mut scope_ranges: Vec<(usize, Vec<Scope>)> = Vec::new();
+
+  for (range, op) in ScopeRangeIterator::new(&ops, line) {
+    scopes.apply(op).map_err(|e| e.to_string())?;
+
+    if !range.is_empty() {
+      scope_ranges.push((range.end, scopes.as_slice().to_vec()));
+    }
+  }
+
+  let mut offset = 0;
+
+  Ok(
+    HighlightIterator::new(&mut state.highlight, &ops, line, highlighter)
+      .map(|(style, text)| {
+        let scopes = scope_ranges
+          .iter()
+          .find(|(end, _)| *end > offset)
+          .map(|(_, scopes)| scopes.clone())
+          .unwrap_or_default();
+        offset += text.len();
+
+        Token {
+          style,
+          scopes,
+          text,
+        }
+      })
+      .collect(),
+  )
+}
+
+fn get_cached_states(key: u64) -> Option<Checkpoints> {
+  FILE_STATES.with(|cache| {
+    let mut cache = cache.borrow_mut();
+    let i = cache.iter().position(|(k, _)| *k == key)?;
+    let entry = cache.remove(i);
+    let states = entry.1.clone();
+    cache.push(entry);
+
+    Some(states)
+  })
+}
+
+fn cache_states(key: u64, states: Checkpoints) {
+  FILE_STATES.with(|cache| {
+    let mut cache = cache.borrow_mut();
+
+    if cache.len() >= MAX_CACHED_FILES {
+      cache.remove(0);
+    }
+    cache.push((key, states));
+  });
 }
 
 // pub fn scale_colour(colour: Color, theme: &ThemeColour) -> Color {
@@ -193,3 +699,111 @@ pub fn colour_to_hue(colour: Color) -> f32 {
 
   hue
 }
+
+#[cfg(test)]
+mod tests {
+  use crate::git::git_types::{HunkLine, HunkLineStatus};
+  use crate::git::queries::syntax_colouring::{
+    clear_linguist_languages, get_linguist_language, Colouring, FileHighlighter,
+    ThemeColour, CHECKPOINT_INTERVAL, MAX_PARSE_LINES,
+  };
+  use crate::git::test_repo::TestRepo;
+
+  #[test]
+  fn test_colour_with_file_context() {
+    let mut colouring = Colouring::new();
+    let text = "fn a() {}\n/*\nlet b = 1;\n*/\n";
+
+    let mut line = HunkLine::header_from_type(HunkLineStatus::Unchanged, 0);
+    line.new_num = Some(3);
+
+    let mut c =
+      colouring.get_colour_line_for_file(&ThemeColour::Light, ".", "a.rs", None);
+    let fragment = c.colour_hunk_line(&line, "let b = 1;\n").unwrap();
+    let fragment_style = fragment[0].style;
+
+    let mut c =
+      colouring.get_colour_line_for_file(&ThemeColour::Light, ".", "a.rs", None);
+    c.set_file_texts(None, Some(text));
+    let parts = c.colour_hunk_line(&line, "let b = 1;\n").unwrap();
+
+    // Inside the comment, so the whole line has one style.
+    assert_eq!(parts.len(), 1);
+    assert_ne!(parts[0].style, fragment_style);
+    assert_eq!(c.token_class(&parts[0]), "hl-comment");
+  }
+
+  #[test]
+  fn test_file_context_parsed_lazily() {
+    let colouring = Colouring::new();
+    let syntax = colouring.syntax_set.find_syntax_by_extension("rs").unwrap();
+    let text = "let a = 1;\n".repeat(CHECKPOINT_INTERVAL * 10);
+
+    let mut file = FileHighlighter::new(&colouring, syntax, &text);
+    file
+      .colour(CHECKPOINT_INTERVAL + 2, "let a = 1;\n")
+      .unwrap();
+    let checkpoints = file.checkpoints.clone().unwrap();
+    assert_eq!(checkpoints.borrow().len(), 2);
+
+    // Another highlighter for the same text, e.g. the next page, shares and extends them.
+    let mut next = FileHighlighter::new(&colouring, syntax, &text);
+    next
+      .colour(CHECKPOINT_INTERVAL * 3 + 1, "let a = 1;\n")
+      .unwrap();
+    assert_eq!(checkpoints.borrow().len(), 4);
+
+    // Going back starts from a checkpoint.
+    assert!(next.colour(2, "let a = 1;\n").is_ok());
+    assert!(next.colour(CHECKPOINT_INTERVAL * 10 + 1, "").is_err());
+  }
+
+  #[test]
+  fn test_large_file_not_parsed() {
+    let mut colouring = Colouring::new();
+    let large = "a\n".repeat(MAX_PARSE_LINES + 1);
+
+    let mut c =
+      colouring.get_colour_line_for_file(&ThemeColour::Light, ".", "a.rs", None);
+    c.set_file_texts(Some(&large), Some("a\n"));
+
+    assert!(c.old_file.is_none());
+    assert!(c.new_file.is_some());
+  }
+
+  #[test]
+  fn test_find_syntax_for_file() {
+    let colouring = Colouring::new();
+    let find = |path: &str, first_line: Option<&str>| {
+      colouring
+        .find_syntax_for_file(".", path, first_line)
+        .map(|s| s.name.clone())
+    };
+
+    assert_eq!(find("src/a.ts", None).as_deref(), Some("TypeScript"));
+    assert_eq!(find("src/a.tsx", None).as_deref(), Some("TypeScriptReact"));
+    assert_eq!(find("Makefile", None).as_deref(), Some("Makefile"));
+    assert_eq!(
+      find("bin/run", Some("#!/usr/bin/env python3")).as_deref(),
+      Some("Python")
+    );
+    assert_eq!(find("bin/run", None), None);
+  }
+
+  #[test]
+  fn test_linguist_language_cache() {
+    let repo = TestRepo::new("linguist_language_cache");
+    let path = repo.path_str();
+    repo.write(".gitattributes", "*.x linguist-language=Rust\n");
+
+    assert_eq!(get_linguist_language(&path, "a.x").as_deref(), Some("Rust"));
+    assert_eq!(get_linguist_language(&path, "a.y"), None);
+
+    // Cached until the repo's status is loaded again.
+    repo.write(".gitattributes", "*.x linguist-language=Go\n");
+    assert_eq!(get_linguist_language(&path, "a.x").as_deref(), Some("Rust"));
+
+    clear_linguist_languages(&path);
+    assert_eq!(get_linguist_language(&path, "a.x").as_deref(), Some("Go"));
+  }
+}
@@ -0,0 +1,59 @@
+use crate::config::{APPLICATION, ORGANISATION, QUALIFIER};
+use crate::dprintln;
+use directories::ProjectDirs;
+use std::path::PathBuf;
+use syntect::highlighting::ThemeSet;
+use syntect::parsing::{SyntaxDefinition, SyntaxSet};
+
+const TYPESCRIPT: &str = include_str!("syntaxes/TypeScript.sublime-syntax");
+const TYPESCRIPT_REACT: &str = include_str!("syntaxes/TypeScriptReact.sublime-syntax");
+
+/*
+syntect's defaults, then our built in grammars, then any .sublime-syntax files in the
+"syntaxes" config dir. Syntaxes added later win when looking up by extension, so user
+files can replace ours.
+ */
+pub fn load_syntax_set() -> SyntaxSet {
+  let mut builder = SyntaxSet::load_defaults_newlines().into_builder();
+
+  for text in [TYPESCRIPT, TYPESCRIPT_REACT] {
+    match SyntaxDefinition::load_from_str(text, true, None) {
+      Ok(syntax) => builder.add(syntax),
+      Err(e) => {
+        dprintln!("Failed to load built in syntax: {}", e);
+      }
+    }
+  }
+
+  if let Some(dir) = get_user_dir("syntaxes") {
+    if let Err(e) = builder.add_from_folder(&dir, true) {
+      dprintln!("Failed to load syntaxes from {:?}: {}", dir, e);
+    }
+  }
+
+  builder.build()
+}
+
+// .tmTheme files in the "themes" config dir are named by their file name.
+pub fn load_theme_set() -> ThemeSet {
+  let mut theme_set = ThemeSet::load_defaults();
+
+  if let Some(dir) = get_user_dir("themes") {
+    if let Err(e) = theme_set.add_from_folder(&dir) {
+      dprintln!("Failed to load themes from {:?}: {}", dir, e);
+    }
+  }
+
+  theme_set
+}
+
+fn get_user_dir(name: &str) -> Option<PathBuf> {
+  let proj_dirs = ProjectDirs::from(QUALIFIER, ORGANISATION, APPLICATION)?;
+  let dir = proj_dirs.config_dir().join(name);
+
+  if dir.is_dir() {
+    Some(dir)
+  } else {
+    None
+  }
+}
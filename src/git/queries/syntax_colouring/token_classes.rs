@@ -0,0 +1,172 @@
+use crate::f;
+use syntect::highlighting::{Color, FontStyle, Highlighter, Theme};
+use syntect::parsing::Scope;
+
+pub const CLASS_PREFIX: &str = "hl-";
+// For tokens that don't match any of CLASSES. Gets the theme's foreground colour.
+pub const TEXT_CLASS: &str = "text";
+
+/*
+Class names and the scopes they cover, most specific first. Each token gets the class of
+its innermost scope that matches. The stylesheet uses the first scope of each class to
+look up the theme colour, so themes don't need to know about our names.
+ */
+const CLASSES: &[(&str, &[&str])] = &[
+  ("comment", &["comment", "punctuation.definition.comment"]),
+  ("string", &["string", "punctuation.definition.string"]),
+  ("escape", &["constant.character.escape"]),
+  ("number", &["constant.numeric"]),
+  ("constant", &["constant"]),
+  ("operator", &["keyword.operator"]),
+  ("keyword", &["keyword"]),
+  ("storage-type", &["storage.type"]),
+  ("storage", &["storage"]),
+  (
+    "function",
+    &[
+      "entity.name.function",
+      "support.function",
+      "variable.function",
+    ],
+  ),
+  (
+    "type",
+    &[
+      "entity.name.type",
+      "entity.name.class",
+      "support.type",
+      "support.class",
+    ],
+  ),
+  ("tag", &["entity.name.tag"]),
+  ("attribute", &["entity.other.attribute-name"]),
+  ("entity", &["entity"]),
+  ("parameter", &["variable.parameter"]),
+  ("variable-language", &["variable.language"]),
+  ("variable", &["variable"]),
+  ("punctuation", &["punctuation"]),
+  ("heading", &["markup.heading"]),
+  ("inserted", &["markup.inserted"]),
+  ("deleted", &["markup.deleted"]),
+  ("invalid", &["invalid"]),
+];
+
+// CLASSES with the scopes parsed, as that's too slow to do per token.
+pub struct TokenClasses {
+  scopes: Vec<(Scope, &'static str)>,
+}
+
+impl TokenClasses {
+  pub fn new() -> Self {
+    let scopes = CLASSES
+      .iter()
+      .flat_map(|(class, scopes)| scopes.iter().map(move |s| (s, *class)))
+      .filter_map(|(s, class)| Some((Scope::new(s).ok()?, class)))
+      .collect();
+
+    Self { scopes }
+  }
+
+  // Without the prefix, e.g. "keyword".
+  pub fn get_class(&self, scope_stack: &[Scope]) -> &'static str {
+    for scope in scope_stack.iter().rev() {
+      for (class_scope, class) in &self.scopes {
+        if class_scope.is_prefix_of(*scope) {
+          return class;
+        }
+      }
+    }
+
+    TEXT_CLASS
+  }
+}
+
+// Real theme colours, unlike colour_to_style.
+pub fn generate_stylesheet(theme: &Theme) -> String {
+  let highlighter = Highlighter::new(theme);
+  let default_style = highlighter.get_default();
+
+  let mut css = f!(
+    ".{}{} {{ color: {}; }}\n",
+    CLASS_PREFIX,
+    TEXT_CLASS,
+    css_colour(default_style.foreground)
+  );
+
+  for (class, scopes) in CLASSES {
+    let Some(scope) = scopes.first().and_then(|s| Scope::new(s).ok()) else {
+      continue;
+    };
+    let style = highlighter.style_for_stack(&[scope]);
+
+    let mut rules = f!("color: {};", css_colour(style.foreground));
+
+    if style.font_style.contains(FontStyle::BOLD) {
+      rules += " font-weight: bold;";
+    }
+    if style.font_style.contains(FontStyle::ITALIC) {
+      rules += " font-style: italic;";
+    }
+    if style.font_style.contains(FontStyle::UNDERLINE) {
+      rules += " text-decoration: underline;";
+    }
+
+    css += &f!(".{}{} {{ {} }}\n", CLASS_PREFIX, class, rules);
+  }
+
+  css
+}
+
+fn css_colour(colour: Color) -> String {
+  let Color { r, g, b, a } = colour;
+
+  if a == 255 {
+    f!("#{:02x}{:02x}{:02x}", r, g, b)
+  } else {
+    f!("rgba({}, {}, {}, {:.2})", r, g, b, a as f32 / 255.)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::git::queries::syntax_colouring::token_classes::{
+    generate_stylesheet, TokenClasses, TEXT_CLASS,
+  };
+  use syntect::highlighting::ThemeSet;
+  use syntect::parsing::Scope;
+
+  fn stack(scopes: &[&str]) -> Vec<Scope> {
+    scopes.iter().map(|s| Scope::new(s).unwrap()).collect()
+  }
+
+  #[test]
+  fn test_get_class() {
+    let classes = TokenClasses::new();
+
+    assert_eq!(
+      classes.get_class(&stack(&["source.rust", "keyword.operator.rust"])),
+      "operator"
+    );
+    assert_eq!(
+      classes.get_class(&stack(&[
+        "source.rust",
+        "string.quoted.double.rust",
+        "punctuation.definition.string.begin.rust"
+      ])),
+      "string"
+    );
+    assert_eq!(
+      classes.get_class(&stack(&["source.rust", "meta.block.rust"])),
+      TEXT_CLASS
+    );
+  }
+
+  #[test]
+  fn test_generate_stylesheet() {
+    let themes = ThemeSet::load_defaults();
+    let css = generate_stylesheet(&themes.themes["base16-ocean.dark"]);
+
+    assert!(css.contains(".hl-text { color: #"));
+    assert!(css.contains(".hl-keyword { color: #"));
+  }
+}
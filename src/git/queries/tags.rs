@@ -0,0 +1,488 @@
+use std::cmp::Ordering;
+
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::git::git_types::{CommitSignature, DateResult, SignatureStatus};
+use crate::git::queries::commits_parsers::P_DATE;
+use crate::git::run_git::{run_git_err, RunGitOptions};
+use crate::parser::standard_parsers::WS;
+use crate::parser::{parse_all, parse_all_err, Parser};
+use crate::server::request_util::R;
+use crate::{and, many, map, until_str};
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct TagDetails {
+  // Matches RefInfo.id, e.g. "refs/tags/v1.0.0"
+  pub id: String,
+  pub name: String,
+  pub commit_id: String,
+  pub annotated: bool,
+  // Tagger info is empty for lightweight tags. Date is the commit date in that case.
+  pub tagger: String,
+  pub email: String,
+  pub date: Option<DateResult>,
+  pub message: String,
+  pub signed: bool,
+  pub signature: Option<CommitSignature>,
+}
+
+#[derive(Debug, Clone, Deserialize, TS)]
+#[ts(export)]
+pub enum TagSort {
+  Version,
+  Date,
+  Name,
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct ReqTagDetailsOptions {
+  pub repo_path: String,
+  // Load all tags if empty.
+  pub tag_names: Vec<String>,
+  pub sort: TagSort,
+  // Runs "git verify-tag" for each signed tag. Slow.
+  pub verify_signatures: bool,
+}
+
+const FIELDS: [&str; 9] = [
+  "%(refname)",
+  "%(refname:short)",
+  "%(objecttype)",
+  "%(objectname)",
+  "%(*objectname)",
+  "%(taggername)",
+  "%(taggeremail:trim)",
+  "%(creatordate:raw)",
+  "%(contents)",
+];
+
+// Separates the fields of each tag. "%00" would be nicer, but messages can contain
+// control characters.
+const SEP: &str = "f3a1c8e2-5b7d-4a6e-9c0f-1d2e3b4a5c6d";
+
+pub fn load_tag_details(options: &ReqTagDetailsOptions) -> R<Vec<TagDetails>> {
+  let ReqTagDetailsOptions {
+    repo_path,
+    tag_names,
+    sort,
+    verify_signatures,
+  } = options;
+
+  let format = format!("--format={}{}", FIELDS.join(SEP), SEP);
+
+  let mut args = vec!["for-each-ref".to_string(), format];
+
+  if tag_names.is_empty() {
+    args.push("refs/tags".to_string());
+  } else {
+    args.extend(tag_names.iter().map(|n| format!("refs/tags/{}", n)));
+  }
+
+  let out = run_git_err(RunGitOptions { repo_path, args })?.stdout;
+
+  let mut tags = parse_all_err(P_TAGS, &out)?;
+
+  if *verify_signatures {
+    for tag in tags.iter_mut().filter(|t| t.signed) {
+      tag.signature = Some(verify_tag(repo_path, &tag.id)?);
+    }
+  }
+
+  sort_tags(&mut tags, sort);
+
+  Ok(tags)
+}
+
+const P_FIELD: Parser<String> = until_str!(SEP);
+
+type PTagResult = (
+  String,
+  String,
+  String,
+  String,
+  String,
+  String,
+  String,
+  String,
+  String,
+  String,
+);
+
+const P_TAG: Parser<TagDetails> = map!(
+  and!(
+    WS, P_FIELD, P_FIELD, P_FIELD, P_FIELD, P_FIELD, P_FIELD, P_FIELD, P_FIELD, P_FIELD
+  ),
+  |result: PTagResult| {
+    let (_, id, name, object_type, object_id, peeled_id, tagger, email, date, contents) =
+      result;
+
+    let annotated = object_type == "tag";
+    // For lightweight tags, contents is the commit message.
+    let (message, signed) = if annotated {
+      split_signature(&contents)
+    } else {
+      (String::new(), false)
+    };
+
+    TagDetails {
+      id,
+      name,
+      commit_id: if annotated { peeled_id } else { object_id },
+      annotated,
+      tagger,
+      email,
+      date: parse_all(P_DATE, &date),
+      message,
+      signed,
+      signature: None,
+    }
+  }
+);
+
+const P_TAGS: Parser<Vec<TagDetails>> = map!(and!(many!(P_TAG), WS), |result: (
+  Vec<TagDetails>,
+  String
+)| { result.0 });
+
+const SIGNATURE_STARTS: [&str; 3] = [
+  "-----BEGIN PGP SIGNATURE-----",
+  "-----BEGIN SSH SIGNATURE-----",
+  "-----BEGIN SIGNED MESSAGE-----",
+];
+
+// The signature is appended to the tag message.
+fn split_signature(contents: &str) -> (String, bool) {
+  for start in SIGNATURE_STARTS {
+    if let Some(i) = contents.find(start) {
+      return (contents[..i].trim_end().to_string(), true);
+    }
+  }
+
+  (contents.trim_end().to_string(), false)
+}
+
+fn verify_tag(repo_path: &str, tag_id: &str) -> R<CommitSignature> {
+  let out = run_git_err(RunGitOptions {
+    repo_path,
+    args: ["verify-tag", "--raw", tag_id],
+  })?;
+
+  Ok(parse_verify_output(&out.stderr))
+}
+
+/*
+For gpg, "--raw" gives us status lines like:
+[GNUPG:] GOODSIG 4AEE18F83AFDEB23 Name <email>
+[GNUPG:] TRUST_UNDEFINED 0 pgp
+
+For ssh:
+Good "git" signature for name@example.com with ED25519 key SHA256:abc...
+ */
+fn parse_verify_output(stderr: &str) -> CommitSignature {
+  let mut signature = CommitSignature {
+    status: SignatureStatus::None,
+    signer: String::new(),
+    key_id: String::new(),
+  };
+  let mut untrusted = false;
+
+  for line in stderr.lines() {
+    if let Some(status_line) = line.strip_prefix("[GNUPG:] ") {
+      let mut parts = status_line.splitn(3, ' ');
+      let keyword = parts.next().unwrap_or("");
+      let key_id = parts.next().unwrap_or("").to_string();
+      let signer = parts.next().unwrap_or("").to_string();
+
+      let status = match keyword {
+        "GOODSIG" => SignatureStatus::Good,
+        "BADSIG" => SignatureStatus::Bad,
+        "EXPSIG" | "EXPKEYSIG" => SignatureStatus::Expired,
+        "REVKEYSIG" => SignatureStatus::Revoked,
+        "ERRSIG" | "NO_PUBKEY" => SignatureStatus::UnknownKey,
+        "TRUST_UNDEFINED" | "TRUST_NEVER" => {
+          untrusted = true;
+          continue;
+        }
+        _ => continue,
+      };
+
+      if signature.status == SignatureStatus::None {
+        signature.status = status;
+        signature.key_id = key_id;
+        signature.signer = signer;
+      }
+    } else if let Some(rest) = line.strip_prefix("Good \"git\" signature for ") {
+      signature.status = SignatureStatus::Good;
+
+      if let Some((signer, key)) = rest.split_once(" with ") {
+        signature.signer = signer.to_string();
+        signature.key_id = key.rsplit(' ').next().unwrap_or("").to_string();
+      }
+    } else if line.contains("No principal matched") || line.contains("allowedSignersFile")
+    {
+      signature.status = SignatureStatus::UnknownKey;
+    } else if line.starts_with("Could not verify signature") {
+      signature.status = SignatureStatus::Bad;
+    }
+  }
+
+  if untrusted && signature.status == SignatureStatus::Good {
+    signature.status = SignatureStatus::Untrusted;
+  }
+
+  signature
+}
+
+fn sort_tags(tags: &mut [TagDetails], sort: &TagSort) {
+  match sort {
+    TagSort::Version => tags.sort_by(|a, b| compare_versions(&b.name, &a.name)),
+    TagSort::Date => tags.sort_by(|a, b| {
+      let a = a.date.as_ref().map(|d| d.ms).unwrap_or(0);
+      let b = b.date.as_ref().map(|d| d.ms).unwrap_or(0);
+      b.cmp(&a)
+    }),
+    TagSort::Name => tags.sort_by(|a, b| a.name.cmp(&b.name)),
+  }
+}
+
+/*
+Semantic version ordering, ignoring a leading "v". E.g.
+v1.2.0-alpha < v1.2.0-alpha.2 < v1.2.0-beta < v1.2.0 < v1.10.0
+
+Names that don't look like versions are ordered before all versions.
+ */
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+  match (parse_version(a), parse_version(b)) {
+    (Some(a), Some(b)) => {
+      let len = a.core.len().max(b.core.len());
+
+      for i in 0..len {
+        let ordering = a.core.get(i).unwrap_or(&0).cmp(b.core.get(i).unwrap_or(&0));
+
+        if ordering != Ordering::Equal {
+          return ordering;
+        }
+      }
+
+      compare_pre_release(&a.pre_release, &b.pre_release)
+    }
+    (Some(_), None) => Ordering::Greater,
+    (None, Some(_)) => Ordering::Less,
+    (None, None) => a.cmp(b),
+  }
+}
+
+struct Version<'a> {
+  core: Vec<u64>,
+  pre_release: Option<&'a str>,
+}
+
+fn parse_version(name: &str) -> Option<Version<'_>> {
+  let name = name
+    .strip_prefix('v')
+    .or_else(|| name.strip_prefix('V'))
+    .unwrap_or(name);
+
+  // Build metadata doesn't affect ordering.
+  let name = name.split('+').next().unwrap_or(name);
+
+  let (core, pre_release) = match name.split_once('-') {
+    Some((core, pre)) => (core, Some(pre)),
+    None => (name, None),
+  };
+
+  let core = core
+    .split('.')
+    .map(|n| n.parse::<u64>().ok())
+    .collect::<Option<Vec<u64>>>()?;
+
+  Some(Version { core, pre_release })
+}
+
+fn compare_pre_release(a: &Option<&str>, b: &Option<&str>) -> Ordering {
+  match (a, b) {
+    (None, None) => Ordering::Equal,
+    // A pre-release comes before the release.
+    (None, Some(_)) => Ordering::Greater,
+    (Some(_), None) => Ordering::Less,
+    (Some(a), Some(b)) => {
+      let mut a_parts = a.split('.');
+      let mut b_parts = b.split('.');
+
+      loop {
+        match (a_parts.next(), b_parts.next()) {
+          (None, None) => return Ordering::Equal,
+          (None, Some(_)) => return Ordering::Less,
+          (Some(_), None) => return Ordering::Greater,
+          (Some(a), Some(b)) => {
+            let ordering = match (a.parse::<u64>(), b.parse::<u64>()) {
+              (Ok(a), Ok(b)) => a.cmp(&b),
+              (Ok(_), Err(_)) => Ordering::Less,
+              (Err(_), Ok(_)) => Ordering::Greater,
+              (Err(_), Err(_)) => a.cmp(b),
+            };
+
+            if ordering != Ordering::Equal {
+              return ordering;
+            }
+          }
+        }
+      }
+    }
+  }
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct DescribeOptions {
+  pub repo_path: String,
+  pub commit_id: String,
+}
+
+// Nearest tag description e.g. "v1.2.0-3-g1a2b3c4". None if there are no tags before
+// this commit.
+pub fn describe_commit(options: &DescribeOptions) -> R<Option<String>> {
+  let DescribeOptions {
+    repo_path,
+    commit_id,
+  } = options;
+
+  let out = run_git_err(RunGitOptions {
+    repo_path,
+    args: ["describe", "--tags", commit_id],
+  })?;
+
+  let description = out.stdout.trim();
+
+  if description.is_empty() {
+    Ok(None)
+  } else {
+    Ok(Some(description.to_string()))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::cmp::Ordering;
+
+  use crate::git::git_types::SignatureStatus;
+  use crate::git::queries::tags::{
+    compare_versions, parse_verify_output, split_signature, P_TAGS, SEP,
+  };
+  use crate::parser::parse_all;
+
+  #[test]
+  fn test_compare_versions() {
+    let mut names = vec![
+      "v1.10.0",
+      "v1.2.0",
+      "v1.2.0-beta",
+      "v1.2.0-alpha.2",
+      "nightly",
+      "v1.2.0-alpha",
+      "1.9",
+    ];
+
+    names.sort_by(|a, b| compare_versions(a, b));
+
+    assert_eq!(
+      names,
+      vec![
+        "nightly",
+        "v1.2.0-alpha",
+        "v1.2.0-alpha.2",
+        "v1.2.0-beta",
+        "v1.2.0",
+        "1.9",
+        "v1.10.0"
+      ]
+    );
+
+    assert_eq!(compare_versions("v1.2", "1.2.0"), Ordering::Equal);
+  }
+
+  #[test]
+  fn test_p_tags() {
+    let annotated = [
+      "refs/tags/v1.0.0",
+      "v1.0.0",
+      "tag",
+      "a3b4f0f5e6c1a0b1c2d3e4f5a6b7c8d9e0f1a2b3",
+      "dd5733ad96082f0f33164bd1e2d72f7540bf7d9f",
+      "Firstname Lastname",
+      "sugto555@gmail.com",
+      "1648863350 +1300",
+      "Release 1.0.0\n\nLots of things.\n",
+    ]
+    .join(SEP);
+
+    let lightweight = [
+      "refs/tags/light",
+      "light",
+      "commit",
+      "dd5733ad96082f0f33164bd1e2d72f7540bf7d9f",
+      "",
+      "",
+      "",
+      "1648863350 +1300",
+      "Commit message\n",
+    ]
+    .join(SEP);
+
+    let out = format!("{annotated}{SEP}\n{lightweight}{SEP}\n");
+
+    let tags = parse_all(P_TAGS, &out);
+
+    assert!(tags.is_some());
+    let tags = tags.unwrap();
+
+    assert_eq!(tags.len(), 2);
+    assert!(tags[0].annotated);
+    assert_eq!(
+      tags[0].commit_id,
+      "dd5733ad96082f0f33164bd1e2d72f7540bf7d9f"
+    );
+    assert_eq!(tags[0].message, "Release 1.0.0\n\nLots of things.");
+    assert_eq!(tags[0].date.as_ref().unwrap().ms, 1648863350000);
+    assert!(!tags[1].annotated);
+    assert!(tags[1].message.is_empty());
+    assert_eq!(
+      tags[1].commit_id,
+      "dd5733ad96082f0f33164bd1e2d72f7540bf7d9f"
+    );
+  }
+
+  #[test]
+  fn test_split_signature() {
+    let (message, signed) =
+      split_signature("Release\n-----BEGIN PGP SIGNATURE-----\n\nabc\n");
+
+    assert!(signed);
+    assert_eq!(message, "Release");
+  }
+
+  #[test]
+  fn test_parse_verify_output() {
+    let gpg = "[GNUPG:] NEWSIG\n\
+      [GNUPG:] GOODSIG 4AEE18F83AFDEB23 Firstname Lastname <a@b.com>\n\
+      [GNUPG:] TRUST_UNDEFINED 0 pgp\n";
+
+    let signature = parse_verify_output(gpg);
+    assert_eq!(signature.status, SignatureStatus::Untrusted);
+    assert_eq!(signature.key_id, "4AEE18F83AFDEB23");
+    assert_eq!(signature.signer, "Firstname Lastname <a@b.com>");
+
+    let ssh = "Good \"git\" signature for a@b.com with ED25519 key SHA256:abc123\n";
+
+    let signature = parse_verify_output(ssh);
+    assert_eq!(signature.status, SignatureStatus::Good);
+    assert_eq!(signature.signer, "a@b.com");
+    assert_eq!(signature.key_id, "SHA256:abc123");
+  }
+}
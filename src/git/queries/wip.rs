@@ -1,18 +1,61 @@
-mod create_hunks;
+pub(crate) mod create_hunks;
+pub(crate) mod file_attributes;
 pub(crate) mod wip_diff;
 mod wip_patch_parsers;
 pub(crate) mod wip_patches;
 use crate::git::store::STORE;
 use crate::server::git_request::ReqOptions;
+use serde::Serialize;
 use std::fs::read_to_string;
+use std::path::Path;
+use ts_rs::TS;
 
 pub fn is_rebase_in_progress(options: &ReqOptions) -> bool {
   if let Ok(path) = STORE.get_repo_path(&options.repo_path) {
-    return path.git_path.join("rebase-merge").exists();
+    let apply_dir = path.git_path.join("rebase-apply");
+
+    // "rebase-apply" without "applying" is "rebase --apply" rather than "git am".
+    return path.git_path.join("rebase-merge").exists()
+      || (apply_dir.exists() && !apply_dir.join("applying").exists());
   }
   false
 }
 
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct MailboxSession {
+  // 1 based index of the patch that stopped, out of total.
+  pub current: u32,
+  pub total: u32,
+  pub subject: String,
+}
+
+// A stopped "git am". "rebase-apply" is also used by "rebase --apply", without "applying".
+pub fn read_mailbox_session(repo_path: &str) -> Option<MailboxSession> {
+  let path = STORE.get_repo_path(repo_path).ok()?;
+  let dir = path.git_path.join("rebase-apply");
+
+  if !dir.join("applying").exists() {
+    return None;
+  }
+
+  let read_num =
+    |name: &str| -> Option<u32> { read_trimmed(&dir.join(name))?.parse().ok() };
+
+  Some(MailboxSession {
+    current: read_num("next")?,
+    total: read_num("last")?,
+    subject: read_trimmed(&dir.join("final-commit"))
+      .and_then(|text| text.lines().next().map(|l| l.to_string()))
+      .unwrap_or_default(),
+  })
+}
+
+fn read_trimmed(path: &Path) -> Option<String> {
+  Some(read_to_string(path).ok()?.trim().to_string())
+}
+
 // // Returns the commit id of the branch we tried to merge
 // // into our current if we have a conflict.
 // pub fn is_merge_in_progress(options: &ReqOptions) -> Option<String> {
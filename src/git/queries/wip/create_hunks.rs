@@ -1,43 +1,50 @@
 use crate::git::git_types::{Hunk, HunkLine, HunkLineStatus, HunkRange};
+use crate::git::queries::hunks::diff_options::DiffOptions;
 use std::cmp::{max, min};
 use HunkLineStatus::{Added, Removed};
 
-pub fn convert_lines_to_hunks(lines: Vec<HunkLine>) -> (Vec<Hunk>, u32) {
+pub fn convert_lines_to_hunks(
+  lines: Vec<HunkLine>,
+  diff_options: &DiffOptions,
+) -> (Vec<Hunk>, u32) {
   let mut hunks = Vec::<Hunk>::new();
   let mut current_hunk = Hunk::new();
   let mut started_making_hunk = false;
 
-  // Hunks should be joined if there's only 6 unchanged lines between them.
+  let context = diff_options.context() as i32;
+  let ignored = find_ignored_changes(&lines, diff_options.ignore_blank_lines);
+
+  // Hunks should be joined if there's only context * 2 unchanged lines between them.
   let mut gap_count = 0;
   let mut patch_size: u32 = 0;
 
   for (i, line) in lines.iter().enumerate() {
     let HunkLine { status, .. } = line;
 
-    if *status == Added || *status == Removed {
+    if (*status == Added || *status == Removed) && !ignored[i] {
       patch_size += 1;
       gap_count = 0;
 
       if !started_making_hunk {
         started_making_hunk = true;
 
-        let start_i = max(0, (i as i32) - 3) as usize;
+        let start_i = max(0, (i as i32) - context) as usize;
         let slice = &lines[start_i..i];
 
         current_hunk.lines.extend_from_slice(slice);
       }
       current_hunk.lines.push(line.clone());
     } else if started_making_hunk {
-      if gap_count < 6 {
+      if gap_count < context * 2 {
         gap_count += 1;
         current_hunk.lines.push(line.clone());
       } else {
-        if gap_count < 3 {
-          current_hunk
-            .lines
-            .extend_from_slice(&lines[i..min(i + (3 - gap_count), lines.len())]);
+        if gap_count < context {
+          current_hunk.lines.extend_from_slice(
+            &lines[i..min(i + (context - gap_count) as usize, lines.len())],
+          );
         } else {
-          for _ in 0..(gap_count - 3) {
+          for _ in 0..(gap_count - context) {
             current_hunk.lines.pop();
           }
         }
@@ -61,6 +68,37 @@ pub fn convert_lines_to_hunks(lines: Vec<HunkLine>) -> (Vec<Hunk>, u32) {
   (hunks, patch_size)
 }
 
+// Like git's --ignore-blank-lines, changes made up only of blank lines don't start a
+// hunk. They are still shown if they are close to other changes.
+fn find_ignored_changes(lines: &[HunkLine], ignore_blank_lines: bool) -> Vec<bool> {
+  let mut ignored = vec![false; lines.len()];
+
+  if !ignore_blank_lines {
+    return ignored;
+  }
+
+  let is_change = |line: &HunkLine| line.status == Added || line.status == Removed;
+  let mut i = 0;
+
+  while i < lines.len() {
+    if !is_change(&lines[i]) {
+      i += 1;
+      continue;
+    }
+
+    let start = i;
+    while i < lines.len() && is_change(&lines[i]) {
+      i += 1;
+    }
+
+    if lines[start..i].iter().all(|l| l.text.trim().is_empty()) {
+      ignored[start..i].fill(true);
+    }
+  }
+
+  ignored
+}
+
 fn set_line_ranges(hunk: &mut Hunk) {
   let Hunk { lines, .. } = hunk;
 
@@ -98,6 +136,7 @@ fn set_indices(hunks: &mut [Hunk]) {
 
 #[cfg(test)]
 mod tests {
+  use crate::git::queries::hunks::diff_options::DiffOptions;
   use crate::git::queries::wip::create_hunks::convert_lines_to_hunks;
   use crate::git::queries::wip::wip_diff::calc_hunk_line_from_text;
   use std::cmp::max;
@@ -136,11 +175,12 @@ export interface AnimationTime {
 }
 ";
 
-    let lines = calc_hunk_line_from_text("", text);
+    let options = DiffOptions::default();
+    let lines = calc_hunk_line_from_text("", text, &options);
 
     assert_eq!(lines.len(), 30);
 
-    let hunks = convert_lines_to_hunks(lines);
+    let hunks = convert_lines_to_hunks(lines, &options);
 
     assert_eq!(hunks.0.len(), 1);
 
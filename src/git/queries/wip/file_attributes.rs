@@ -0,0 +1,156 @@
+use std::collections::HashSet;
+
+use encoding_rs::Encoding;
+
+use crate::git::run_git::{run_git_err, run_git_stdin, RunGitOptions};
+
+// The .gitattributes that change how a working tree file compares with its blob.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FileAttributes {
+  // The working tree file is in this encoding, its blobs are UTF-8.
+  pub working_tree_encoding: Option<&'static Encoding>,
+  // "-text". Git doesn't convert line endings, so they're part of the diff.
+  pub text_unset: bool,
+  // "-diff" or "binary".
+  pub binary: bool,
+}
+
+/*
+"eol" isn't needed. It only changes what git writes to the working tree, and we match
+the line endings of the file there anyway.
+ */
+const ATTRIBUTES: [&str; 3] = ["working-tree-encoding", "text", "diff"];
+
+// Falls back to no attributes if check-attr fails, as we did before reading them.
+pub fn load_file_attributes(repo_path: &str, file_path: &str) -> FileAttributes {
+  let mut args = vec!["check-attr", "-z"];
+  args.extend(ATTRIBUTES);
+  args.extend(["--", file_path]);
+
+  match run_git_err(RunGitOptions { repo_path, args }) {
+    Ok(out) => attributes_from_values(&parse_check_attr(&out.stdout)),
+    Err(_) => FileAttributes::default(),
+  }
+}
+
+/*
+Paths marked as binary, from one check-attr call for all of them. They're given on stdin,
+as there could be too many for the command line.
+ */
+pub fn load_binary_files(repo_path: &str, file_paths: &[&str]) -> HashSet<String> {
+  if file_paths.is_empty() {
+    return HashSet::new();
+  }
+
+  let mut input = Vec::new();
+  for path in file_paths {
+    input.extend(path.as_bytes());
+    input.push(0);
+  }
+
+  let Ok(out) = run_git_stdin(
+    RunGitOptions {
+      repo_path,
+      args: ["check-attr", "-z", "--stdin", "diff"],
+    },
+    input,
+  ) else {
+    return HashSet::new();
+  };
+
+  parse_check_attr(&out.stdout)
+    .into_iter()
+    .filter(|(_, _, value)| *value == "unset")
+    .map(|(path, _, _)| path.to_string())
+    .collect()
+}
+
+// Without "text" set either way, line endings are still normalised as they were before.
+fn attributes_from_values(values: &[(&str, &str, &str)]) -> FileAttributes {
+  let mut attributes = FileAttributes::default();
+
+  for (_, name, value) in values {
+    match *name {
+      "working-tree-encoding" => {
+        attributes.working_tree_encoding = match *value {
+          "unspecified" | "set" | "unset" => None,
+          // Git takes iconv names like "UTF-16LE-BOM". Decoding handles the BOM anyway.
+          label => {
+            let label = label.to_uppercase();
+            let label = label.strip_suffix("-BOM").unwrap_or(&label);
+
+            Encoding::for_label(label.as_bytes())
+          }
+        }
+      }
+      "text" => attributes.text_unset = *value == "unset",
+      "diff" => attributes.binary = *value == "unset",
+      _ => {}
+    }
+  }
+
+  attributes
+}
+
+// "<path>\0<attribute>\0<value>\0" for each path and attribute.
+fn parse_check_attr(out: &str) -> Vec<(&str, &str, &str)> {
+  let parts: Vec<&str> = out.split('\0').collect();
+
+  parts
+    .chunks_exact(3)
+    .map(|chunk| (chunk[0], chunk[1], chunk[2]))
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use encoding_rs::UTF_16LE;
+
+  use crate::git::queries::wip::file_attributes::{
+    attributes_from_values, load_binary_files, parse_check_attr, FileAttributes,
+  };
+  use crate::git::test_repo::TestRepo;
+
+  #[test]
+  fn test_attributes_from_check_attr() {
+    let out = "a.txt\0working-tree-encoding\0UTF-16LE\0a.txt\0text\0unset\0\
+      a.txt\0eol\0unspecified\0a.txt\0diff\0unset\0";
+
+    assert_eq!(
+      attributes_from_values(&parse_check_attr(out)),
+      FileAttributes {
+        working_tree_encoding: Some(UTF_16LE),
+        text_unset: true,
+        binary: true,
+      }
+    );
+
+    let out = "b.txt\0working-tree-encoding\0unspecified\0b.txt\0text\0auto\0\
+      b.txt\0eol\0crlf\0b.txt\0diff\0set\0";
+
+    assert_eq!(
+      attributes_from_values(&parse_check_attr(out)),
+      FileAttributes::default()
+    );
+
+    let out = "c.txt\0working-tree-encoding\0UTF-16LE-BOM\0";
+    assert_eq!(
+      attributes_from_values(&parse_check_attr(out)).working_tree_encoding,
+      Some(UTF_16LE)
+    );
+  }
+
+  #[test]
+  fn test_load_binary_files() {
+    let repo = TestRepo::new("load_binary_files");
+    repo.write(".gitattributes", "*.bin binary\n*.nd -diff\n");
+
+    let mut binary: Vec<String> =
+      load_binary_files(&repo.path_str(), &["a.bin", "b.txt", "dir/c d.nd"])
+        .into_iter()
+        .collect();
+    binary.sort();
+
+    assert_eq!(binary, ["a.bin", "dir/c d.nd"]);
+  }
+}
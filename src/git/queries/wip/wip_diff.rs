@@ -1,21 +1,28 @@
-use chardetng::EncodingDetector;
+use encoding_rs::{Encoding, UTF_8};
 use std::fs::read;
 use std::ops::Add;
 use std::path::Path;
 
 use serde::{Deserialize, Serialize};
-use similar::{ChangeTag, TextDiff};
+use similar::{capture_diff_slices, DiffTag};
 use ts_rs::TS;
 
+use crate::git::git_types::HunkLineStatus::{Added, Removed, Unchanged};
 use crate::git::git_types::{Hunk, HunkLine, HunkLineStatus, WipPatch, WipPatchType};
+use crate::git::queries::hunks::diff_options::DiffOptions;
+use crate::git::queries::hunks::inline_changes::add_inline_changes;
+use crate::git::queries::hunks::line_warnings::{add_line_warnings, WhitespaceRules};
 use crate::git::queries::hunks::load_hunks::flatten_hunks_split;
+use crate::git::queries::hunks::moved_lines::detect_moved_lines_in_file;
 use crate::git::queries::refs::head_info::calc_head_info;
 use crate::git::queries::wip::create_hunks::convert_lines_to_hunks;
-use crate::git::run_git::{run_git_err, RunGitOptions};
+use crate::git::queries::wip::file_attributes::{load_file_attributes, FileAttributes};
+use crate::git::run_git::{run_git_buffer, RunGitOptions};
 use crate::parser::standard_parsers::{LINE_END, WS_STR};
 use crate::parser::{parse_all, Parser};
 use crate::server::git_request::ReqOptions;
 use crate::server::request_util::R;
+use crate::util::text_encoding::decode_text;
 use crate::{and, or, rep_parser_sep, until_parser_keep_happy};
 
 #[derive(Debug, Deserialize, TS)]
@@ -25,11 +32,44 @@ pub struct ReqWipHunksOptions {
   pub repo_path: String,
   pub patch: WipPatch,
   pub head_commit: Option<String>,
+  #[serde(default)]
+  pub diff_options: DiffOptions,
+  #[serde(default)]
+  pub kind: WipDiffKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize, TS)]
+#[ts(export)]
+pub enum WipDiffKind {
+  // HEAD against the working copy.
+  #[default]
+  Combined,
+  // HEAD against the index.
+  Staged,
+  // The index against the working copy.
+  Unstaged,
 }
 
 pub fn load_wip_hunks(options: &ReqWipHunksOptions) -> R<(Vec<Hunk>, u32, bool)> {
-  let (lines, valid_utf8) = load_wip_hunk_lines(options)?;
-  let (hunks, patch_size) = convert_lines_to_hunks(lines);
+  let attributes = load_file_attributes(&options.repo_path, &options.patch.new_file);
+
+  load_wip_hunks_with_attributes(options, &attributes)
+}
+
+// For callers that also need load_wip_texts, so the attributes are only loaded once.
+pub fn load_wip_hunks_with_attributes(
+  options: &ReqWipHunksOptions,
+  attributes: &FileAttributes,
+) -> R<(Vec<Hunk>, u32, bool)> {
+  let (lines, valid_utf8) = load_wip_hunk_lines_with_attributes(options, attributes)?;
+  let (mut hunks, patch_size) = convert_lines_to_hunks(lines, &options.diff_options);
+
+  if options.diff_options.detect_moved {
+    let WipPatch {
+      old_file, new_file, ..
+    } = &options.patch;
+    detect_moved_lines_in_file(old_file, new_file, &mut hunks);
+  }
 
   Ok((hunks, patch_size, valid_utf8))
 }
@@ -58,55 +98,243 @@ pub fn load_wip_hunks_split(options: &ReqWipHunksOptions) -> R<WipHunksSplit> {
 }
 
 pub fn load_wip_hunk_lines(options: &ReqWipHunksOptions) -> R<(Vec<HunkLine>, bool)> {
+  let attributes = load_file_attributes(&options.repo_path, &options.patch.new_file);
+
+  load_wip_hunk_lines_with_attributes(options, &attributes)
+}
+
+pub fn load_wip_hunk_lines_with_attributes(
+  options: &ReqWipHunksOptions,
+  attributes: &FileAttributes,
+) -> R<(Vec<HunkLine>, bool)> {
+  let (mut lines, valid_utf8) = calc_wip_hunk_lines(options, attributes)?;
+
+  if let Some(rules) = WhitespaceRules::from_config(&options.repo_path) {
+    add_line_warnings(&mut lines, &rules);
+  }
+
+  Ok((lines, valid_utf8))
+}
+
+fn calc_wip_hunk_lines(
+  options: &ReqWipHunksOptions,
+  attributes: &FileAttributes,
+) -> R<(Vec<HunkLine>, bool)> {
+  if options.patch.is_image || options.patch.is_binary {
+    return Ok((Vec::new(), true));
+  }
+
+  let WipTexts {
+    old,
+    new,
+    valid_utf8,
+  } = load_wip_texts(options, attributes)?;
+
+  Ok((
+    calc_hunk_line_from_text(&old, &new, &options.diff_options),
+    valid_utf8,
+  ))
+}
+
+// Both sides of a WIP diff. Empty if a side doesn't exist, e.g. for an added file.
+pub struct WipTexts {
+  pub old: String,
+  pub new: String,
+  pub valid_utf8: bool,
+}
+
+pub fn load_wip_texts(
+  options: &ReqWipHunksOptions,
+  attributes: &FileAttributes,
+) -> R<WipTexts> {
+  if attributes.binary {
+    return Ok(WipTexts {
+      old: String::new(),
+      new: String::new(),
+      valid_utf8: true,
+    });
+  }
+
+  match options.kind {
+    WipDiffKind::Combined => load_combined_texts(options, attributes),
+    WipDiffKind::Staged => load_staged_texts(options, attributes),
+    WipDiffKind::Unstaged => load_unstaged_texts(options, attributes),
+  }
+}
+
+fn load_combined_texts(
+  options: &ReqWipHunksOptions,
+  attributes: &FileAttributes,
+) -> R<WipTexts> {
   let ReqWipHunksOptions {
     patch,
     repo_path,
     head_commit,
-  } = &options;
+    ..
+  } = options;
   let WipPatch {
     new_file,
-    is_image,
     patch_type,
     ..
   } = patch;
 
-  if *is_image {
-    return Ok((Vec::new(), true));
-  }
-
   let head_commit = ensure_head_commit(head_commit, repo_path);
 
   if *patch_type == WipPatchType::A || head_commit.is_none() {
-    let new_file_info = load_file(repo_path, new_file)?;
+    let new_file_info = load_file_with_attributes(repo_path, new_file, attributes)?;
 
-    return Ok((
-      calc_hunk_line_from_text("", &new_file_info.text),
-      new_file_info.valid_utf8,
-    ));
+    return Ok(WipTexts {
+      old: String::new(),
+      new: new_file_info.text,
+      valid_utf8: new_file_info.valid_utf8,
+    });
   }
 
   if let Some(commit) = head_commit {
-    let mut old_text =
-      load_unchanged_file(repo_path, patch, &commit).unwrap_or(String::from(""));
+    let old_blob = load_blob(repo_path, &format!("{}:{}", commit, &patch.old_file));
 
     if *patch_type == WipPatchType::D {
-      return Ok((calc_hunk_line_from_text(&old_text, ""), true));
+      return Ok(WipTexts {
+        old: decode_blob(&old_blob, attributes, None).0,
+        new: String::new(),
+        valid_utf8: true,
+      });
+    }
+
+    let new_file_info = load_file_with_attributes(repo_path, new_file, attributes)?;
+    let (old_text, _) = decode_blob(&old_blob, attributes, Some(new_file_info.encoding));
+
+    return Ok(WipTexts {
+      old: match_line_endings(old_text, &new_file_info, attributes),
+      new: new_file_info.text,
+      valid_utf8: new_file_info.valid_utf8,
+    });
+  }
+
+  Ok(WipTexts {
+    old: String::new(),
+    new: String::new(),
+    valid_utf8: true,
+  })
+}
+
+fn load_staged_texts(
+  options: &ReqWipHunksOptions,
+  attributes: &FileAttributes,
+) -> R<WipTexts> {
+  let ReqWipHunksOptions {
+    patch,
+    repo_path,
+    head_commit,
+    ..
+  } = options;
+
+  let (new, encoding) = if patch.staged_type == WipPatchType::D {
+    (String::new(), None)
+  } else {
+    let blob = load_blob(repo_path, &format!(":{}", patch.new_file));
+    let (text, encoding) = decode_blob(&blob, attributes, None);
+
+    (text, Some(encoding))
+  };
+
+  let old = match ensure_head_commit(head_commit, repo_path) {
+    Some(commit) if patch.staged_type != WipPatchType::A => {
+      let blob = load_blob(repo_path, &format!("{}:{}", commit, patch.old_file));
+
+      decode_blob(&blob, attributes, encoding).0
     }
+    _ => String::new(),
+  };
 
-    let new_file_info = load_file(repo_path, new_file)?;
+  Ok(WipTexts {
+    old,
+    new,
+    valid_utf8: true,
+  })
+}
+
+fn load_unstaged_texts(
+  options: &ReqWipHunksOptions,
+  attributes: &FileAttributes,
+) -> R<WipTexts> {
+  let ReqWipHunksOptions {
+    patch, repo_path, ..
+  } = options;
+
+  // Untracked files aren't in the index.
+  if patch.un_staged_type == WipPatchType::Question {
+    let new_file_info =
+      load_file_with_attributes(repo_path, &patch.new_file, attributes)?;
+
+    return Ok(WipTexts {
+      old: String::new(),
+      new: new_file_info.text,
+      valid_utf8: new_file_info.valid_utf8,
+    });
+  }
 
-    old_text = switch_to_line_ending(old_text, &new_file_info.line_ending);
+  let old_blob = load_blob(repo_path, &format!(":{}", patch.new_file));
 
-    return Ok((
-      calc_hunk_line_from_text(&old_text, &new_file_info.text),
-      new_file_info.valid_utf8,
-    ));
+  if patch.un_staged_type == WipPatchType::D {
+    return Ok(WipTexts {
+      old: decode_blob(&old_blob, attributes, None).0,
+      new: String::new(),
+      valid_utf8: true,
+    });
   }
 
-  Ok((Vec::new(), true))
+  let new_file_info = load_file_with_attributes(repo_path, &patch.new_file, attributes)?;
+  let (old, _) = decode_blob(&old_blob, attributes, Some(new_file_info.encoding));
+
+  Ok(WipTexts {
+    old: match_line_endings(old, &new_file_info, attributes),
+    new: new_file_info.text,
+    valid_utf8: new_file_info.valid_utf8,
+  })
 }
 
-fn ensure_head_commit(head: &Option<String>, repo_path: &str) -> Option<String> {
+// Empty if the object doesn't exist, as the file would be.
+fn load_blob(repo_path: &str, object: &str) -> Vec<u8> {
+  run_git_buffer(RunGitOptions {
+    repo_path,
+    args: ["show", object],
+  })
+  .unwrap_or_default()
+}
+
+/*
+Blobs are UTF-8 when the file has a working-tree-encoding. Otherwise they're in the same
+encoding as the other side if we know it, so both sides are decoded the same way.
+ */
+fn decode_blob(
+  bytes: &[u8],
+  attributes: &FileAttributes,
+  other_side: Option<&'static Encoding>,
+) -> (String, &'static Encoding) {
+  if attributes.working_tree_encoding.is_some() {
+    return (String::from_utf8_lossy(bytes).into_owned(), UTF_8);
+  }
+
+  let decoded = decode_text(bytes, other_side.filter(|e| *e != UTF_8));
+
+  (decoded.text, decoded.encoding)
+}
+
+// With "-text" git keeps line endings as they are, so changes to them are shown.
+fn match_line_endings(
+  old: String,
+  new: &FileInfo,
+  attributes: &FileAttributes,
+) -> String {
+  if attributes.text_unset {
+    old
+  } else {
+    switch_to_line_ending(old, &new.line_ending)
+  }
+}
+
+pub fn ensure_head_commit(head: &Option<String>, repo_path: &str) -> Option<String> {
   if head.is_none() {
     return Some(
       calc_head_info(&ReqOptions {
@@ -120,46 +348,48 @@ fn ensure_head_commit(head: &Option<String>, repo_path: &str) -> Option<String>
   head.clone()
 }
 
-struct FileInfo {
-  text: String,
-  line_ending: String,
-  valid_utf8: bool,
+pub struct FileInfo {
+  pub text: String,
+  pub line_ending: String,
+  pub valid_utf8: bool,
+  pub encoding: &'static Encoding,
 }
 
-fn load_file(repo_path: &str, file_path: &str) -> R<FileInfo> {
+fn load_file_with_attributes(
+  repo_path: &str,
+  file_path: &str,
+  attributes: &FileAttributes,
+) -> R<FileInfo> {
   let path = Path::new(repo_path).join(file_path);
   let bytes = read(path)?;
 
-  if let Ok(text) = String::from_utf8(bytes.clone()) {
-    let line_ending = detect_new_line(&text);
+  // A declared encoding isn't a guess, so it counts as valid.
+  let (text, encoding, valid_utf8) = match attributes.working_tree_encoding {
+    Some(encoding) => (encoding.decode(&bytes).0.into_owned(), encoding, true),
+    None => {
+      let decoded = decode_text(&bytes, None);
 
-    if !text.ends_with(&line_ending) {
-      return Ok(FileInfo {
-        text: text.add(&line_ending),
-        line_ending,
-        valid_utf8: true,
-      });
+      (decoded.text, decoded.encoding, decoded.encoding == UTF_8)
     }
+  };
 
-    Ok(FileInfo {
-      text,
-      line_ending,
-      valid_utf8: true,
-    })
-  } else {
-    let mut decoder = EncodingDetector::new();
-    decoder.feed(&bytes, true);
-    let encoding = decoder.guess(None, true);
-    let content = encoding.decode(&bytes).0;
-    let text = content.into_owned();
-    let line_ending = detect_new_line(&text);
-
-    Ok(FileInfo {
-      text,
+  let line_ending = detect_new_line(&text);
+
+  if valid_utf8 && !text.ends_with(&line_ending) {
+    return Ok(FileInfo {
+      text: text.add(&line_ending),
       line_ending,
-      valid_utf8: false,
-    })
+      valid_utf8,
+      encoding,
+    });
   }
+
+  Ok(FileInfo {
+    text,
+    line_ending,
+    valid_utf8,
+    encoding,
+  })
 }
 
 fn detect_new_line(text: &str) -> String {
@@ -194,79 +424,88 @@ fn switch_to_line_ending(text: String, line_ending: &str) -> String {
   text
 }
 
-pub fn calc_hunk_line_from_text(a: &str, b: &str) -> Vec<HunkLine> {
-  let diff = TextDiff::from_lines(a, b);
+/*
+Lines are compared with DiffOptions::compare_key, so we can ignore whitespace the same
+way git does. Like git, unchanged lines show the new text.
+ */
+pub fn calc_hunk_line_from_text(
+  a: &str,
+  b: &str,
+  diff_options: &DiffOptions,
+) -> Vec<HunkLine> {
+  let old_lines: Vec<&str> = a.split_inclusive('\n').collect();
+  let new_lines: Vec<&str> = b.split_inclusive('\n').collect();
 
-  let mut lines = Vec::<HunkLine>::new();
+  let key = |line: &&str| diff_options.compare_key(line.trim_end_matches(['\r', '\n']));
+  let old_keys: Vec<String> = old_lines.iter().map(key).collect();
+  let new_keys: Vec<String> = new_lines.iter().map(key).collect();
 
-  let mut running_old_num = 0;
-  let mut running_new_num = 0;
+  let ops = capture_diff_slices(diff_options.similar_algorithm(), &old_keys, &new_keys);
 
-  for change in diff.iter_all_changes() {
-    let mut old_num: Option<i32> = None;
-    let mut new_num: Option<i32> = None;
+  let mut lines = Vec::<HunkLine>::new();
 
-    match change.tag() {
-      ChangeTag::Insert => {
-        running_new_num += 1;
-        new_num = Some(running_new_num);
-      }
-      ChangeTag::Delete => {
-        running_old_num += 1;
-        old_num = Some(running_old_num);
+  for op in ops {
+    let (tag, old_range, new_range) = op.as_tag_tuple();
+
+    match tag {
+      DiffTag::Equal => {
+        for (o, n) in old_range.zip(new_range) {
+          push_line(&mut lines, new_lines[n], Unchanged, Some(o), Some(n));
+        }
       }
-      ChangeTag::Equal => {
-        running_old_num += 1;
-        running_new_num += 1;
-        old_num = Some(running_old_num);
-        new_num = Some(running_new_num);
+      DiffTag::Delete | DiffTag::Insert | DiffTag::Replace => {
+        for o in old_range {
+          push_line(&mut lines, old_lines[o], Removed, Some(o), None);
+        }
+        for n in new_range {
+          push_line(&mut lines, new_lines[n], Added, None, Some(n));
+        }
       }
     }
-
-    let line_text = change.to_string();
-    let parts = parse_all(LINE_PARSER, &line_text).unwrap_or((String::from(""), ""));
-
-    lines.push(HunkLine {
-      text: parts.0,
-      line_ending: parts.1.to_string(),
-      status: get_status_from_change_tag(&change.tag()),
-      hunk_index: -1,
-      index: lines.len() as u32,
-      old_num,
-      new_num,
-    });
   }
 
-  lines
-}
+  add_inline_changes(&mut lines);
+  add_line_warnings(&mut lines, &WhitespaceRules::default());
 
-fn get_status_from_change_tag(tag: &ChangeTag) -> HunkLineStatus {
-  match tag {
-    ChangeTag::Insert => HunkLineStatus::Added,
-    ChangeTag::Delete => HunkLineStatus::Removed,
-    ChangeTag::Equal => HunkLineStatus::Unchanged,
-  }
+  lines
 }
 
-fn load_unchanged_file(
-  repo_path: &String,
-  patch: &WipPatch,
-  head_commit: &str,
-) -> R<String> {
-  Ok(
-    run_git_err(RunGitOptions {
-      repo_path,
-      args: ["show", &format!("{}:{}", head_commit, &patch.old_file)],
-    })?
-    .stdout,
-  )
+// Takes 0 based line indices.
+fn push_line(
+  lines: &mut Vec<HunkLine>,
+  line: &str,
+  status: HunkLineStatus,
+  old_index: Option<usize>,
+  new_index: Option<usize>,
+) {
+  let parts = parse_all(LINE_PARSER, line).unwrap_or((String::from(""), ""));
+
+  lines.push(HunkLine {
+    text: parts.0,
+    line_ending: parts.1.to_string(),
+    status,
+    hunk_index: -1,
+    index: lines.len() as u32,
+    old_num: old_index.map(|i| i as i32 + 1),
+    new_num: new_index.map(|i| i as i32 + 1),
+    changed_ranges: Vec::new(),
+    moved: None,
+    warnings: Vec::new(),
+  });
 }
 
 #[cfg(test)]
 mod tests {
+  use encoding_rs::{UTF_16LE, UTF_8, WINDOWS_1252};
+
+  use crate::git::git_types::{WipPatch, WipPatchType};
+  use crate::git::queries::hunks::diff_options::{DiffOptions, WhitespaceMode};
+  use crate::git::queries::wip::file_attributes::{load_file_attributes, FileAttributes};
   use crate::git::queries::wip::wip_diff::{
-    calc_hunk_line_from_text, detect_new_line, LINES_PARSER,
+    calc_hunk_line_from_text, decode_blob, detect_new_line, load_blob, load_wip_texts,
+    ReqWipHunksOptions, WipDiffKind, WipTexts, LINES_PARSER,
   };
+  use crate::git::test_repo::TestRepo;
   use crate::parser::parse_all;
 
   #[test]
@@ -303,11 +542,28 @@ export interface AnimationTime {
 }
 ";
 
-    let lines = calc_hunk_line_from_text("", text);
+    let lines = calc_hunk_line_from_text("", text, &DiffOptions::default());
 
     assert_eq!(lines.len(), 30);
   }
 
+  #[test]
+  fn test_calc_hunk_line_ignore_whitespace() {
+    let old = "a\n  b\nc\n";
+    let new = "a\nb \nc\n";
+
+    let mut options = DiffOptions::default();
+    let lines = calc_hunk_line_from_text(old, new, &options);
+    assert_eq!(lines.len(), 4);
+
+    options.whitespace = WhitespaceMode::IgnoreAll;
+    let lines = calc_hunk_line_from_text(old, new, &options);
+    assert_eq!(lines.len(), 3);
+    // Unchanged lines show the new text, like git.
+    assert_eq!(lines[1].text, "b ");
+    assert_eq!(lines[1].old_num, Some(2));
+  }
+
   #[test]
   fn test_detect_new_line() {
     assert_eq!(detect_new_line("\r\na"), "\r\n");
@@ -335,4 +591,168 @@ export interface AnimationTime {
 
     assert!(res.is_some());
   }
+
+  fn wip_patch(
+    file: &str,
+    staged_type: WipPatchType,
+    un_staged_type: WipPatchType,
+  ) -> WipPatch {
+    WipPatch {
+      old_file: file.to_string(),
+      new_file: file.to_string(),
+      patch_type: WipPatchType::M,
+      staged_type,
+      un_staged_type,
+      conflicted: false,
+      id: file.to_string(),
+      is_image: false,
+      is_binary: false,
+    }
+  }
+
+  fn texts(
+    repo: &TestRepo,
+    head: &str,
+    patch: WipPatch,
+    kind: WipDiffKind,
+  ) -> (String, String) {
+    let attributes = load_file_attributes(&repo.path_str(), &patch.new_file);
+    let options = ReqWipHunksOptions {
+      repo_path: repo.path_str(),
+      patch,
+      head_commit: Some(head.to_string()),
+      diff_options: DiffOptions::default(),
+      kind,
+    };
+    let WipTexts { old, new, .. } = load_wip_texts(&options, &attributes).unwrap();
+
+    (old, new)
+  }
+
+  #[test]
+  fn test_load_wip_texts_by_kind() {
+    let repo = TestRepo::new("load_wip_texts_by_kind");
+    repo.write("a.txt", "head\n");
+    let head = repo.commit("init");
+
+    repo.write("a.txt", "index\n");
+    repo.git(&["add", "a.txt"]);
+    repo.write("a.txt", "working\n");
+
+    let patch = wip_patch("a.txt", WipPatchType::M, WipPatchType::M);
+    let text = |s: &str| s.to_string();
+
+    assert_eq!(
+      texts(&repo, &head, patch.clone(), WipDiffKind::Combined),
+      (text("head\n"), text("working\n"))
+    );
+    assert_eq!(
+      texts(&repo, &head, patch.clone(), WipDiffKind::Staged),
+      (text("head\n"), text("index\n"))
+    );
+    assert_eq!(
+      texts(&repo, &head, patch, WipDiffKind::Unstaged),
+      (text("index\n"), text("working\n"))
+    );
+
+    // Added in the index, then deleted from the working copy.
+    repo.write("b.txt", "new\n");
+    repo.git(&["add", "b.txt"]);
+    std::fs::remove_file(repo.path.join("b.txt")).unwrap();
+
+    let patch = wip_patch("b.txt", WipPatchType::A, WipPatchType::D);
+
+    assert_eq!(
+      texts(&repo, &head, patch.clone(), WipDiffKind::Staged),
+      (text(""), text("new\n"))
+    );
+    assert_eq!(
+      texts(&repo, &head, patch, WipDiffKind::Unstaged),
+      (text("new\n"), text(""))
+    );
+
+    // Untracked files aren't in the index.
+    repo.write("c.txt", "untracked\n");
+    let patch = wip_patch("c.txt", WipPatchType::Question, WipPatchType::Question);
+
+    assert_eq!(
+      texts(&repo, &head, patch, WipDiffKind::Unstaged),
+      (text(""), text("untracked\n"))
+    );
+  }
+
+  #[test]
+  fn test_load_blob() {
+    let repo = TestRepo::new("load_blob");
+    repo.write("a.txt", "committed\n");
+    let head = repo.commit("init");
+
+    repo.write("a.txt", "staged\n");
+    repo.git(&["add", "a.txt"]);
+
+    let path = repo.path_str();
+    assert_eq!(load_blob(&path, &format!("{}:a.txt", head)), b"committed\n");
+    assert_eq!(load_blob(&path, ":a.txt"), b"staged\n");
+    assert!(load_blob(&path, ":missing.txt").is_empty());
+  }
+
+  #[test]
+  fn test_decode_blob() {
+    let declared = FileAttributes {
+      working_tree_encoding: Some(UTF_16LE),
+      ..FileAttributes::default()
+    };
+    // Git converts these blobs to UTF-8.
+    assert_eq!(
+      decode_blob(b"caf\xC3\xA9\n", &declared, None),
+      ("café\n".to_string(), UTF_8)
+    );
+
+    let none = FileAttributes::default();
+    assert_eq!(
+      decode_blob(b"caf\xE9\n", &none, Some(WINDOWS_1252)),
+      ("café\n".to_string(), WINDOWS_1252)
+    );
+    // UTF-8 on the other side isn't a hint for bytes that aren't.
+    assert_ne!(decode_blob(b"caf\xE9\n", &none, Some(UTF_8)).1, UTF_8);
+  }
+
+  #[test]
+  fn test_load_wip_texts_with_attributes() {
+    let repo = TestRepo::new("load_wip_texts_with_attributes");
+    repo.write(
+      ".gitattributes",
+      "*.u16 working-tree-encoding=UTF-16LE-BOM\n*.raw -text\n",
+    );
+    let utf16 = |text: &str| {
+      let mut bytes = vec![0xFF, 0xFE];
+      bytes.extend(text.encode_utf16().flat_map(|u| u.to_le_bytes()));
+      bytes
+    };
+
+    repo.write("a.u16", utf16("a\nb\n"));
+    repo.write("b.raw", "a\nb\n");
+    repo.write("c.txt", "a\nb\n");
+    let head = repo.commit("init");
+
+    repo.write("a.u16", utf16("a\nc\n"));
+    repo.write("b.raw", "a\r\nb\r\nc\r\n");
+    repo.write("c.txt", "a\r\nb\r\nc\r\n");
+
+    let texts = |file: &str| {
+      let patch = wip_patch(file, WipPatchType::Empty, WipPatchType::M);
+      texts(&repo, &head, patch, WipDiffKind::Combined)
+    };
+    let text = |s: &str| s.to_string();
+
+    // The blob is UTF-8 and the file UTF-16.
+    assert_eq!(texts("a.u16"), (text("a\nb\n"), text("a\nc\n")));
+    // With "-text" the old line endings are kept, so the change to them shows.
+    assert_eq!(texts("b.raw"), (text("a\nb\n"), text("a\r\nb\r\nc\r\n")));
+    // Otherwise they're made to match, as git would normalise them.
+    assert_eq!(
+      texts("c.txt"),
+      (text("a\r\nb\r\n"), text("a\r\nb\r\nc\r\n"))
+    );
+  }
 }
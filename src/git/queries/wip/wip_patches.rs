@@ -1,5 +1,6 @@
 use crate::git::git_types::{WipPatch, WipPatchType};
 use crate::git::queries::patches::file_is_image;
+use crate::git::queries::wip::file_attributes::load_binary_files;
 use crate::git::queries::wip::read_merge_head;
 use crate::git::queries::wip::wip_patch_parsers::P_WIP_PATCHES;
 use crate::git::run_git::RunGitOptions;
@@ -33,7 +34,14 @@ pub fn load_wip_patches(options: &ReqOptions) -> R<WipPatches> {
 
   let info = parse_all_err(P_WIP_PATCHES, &stdout)?;
 
-  let (patches, conflicted) = get_patches_from_info(info);
+  let (mut patches, conflicted) = get_patches_from_info(info);
+
+  let paths: Vec<&str> = patches.iter().map(|p| p.new_file.as_str()).collect();
+  let binary_files = load_binary_files(&options.repo_path, &paths);
+
+  for patch in patches.iter_mut() {
+    patch.is_binary = binary_files.contains(&patch.new_file);
+  }
 
   if conflicted {
     if let Some(id) = read_merge_head(&options.repo_path) {
@@ -77,6 +85,7 @@ fn get_patches_from_info(info: Vec<WipPatchInfo>) -> (Vec<WipPatch>, bool) {
       conflicted,
       id: format!("{}{}", &new_file, patch_type),
       is_image: file_is_image(&new_file),
+      is_binary: false,
     })
   }
 
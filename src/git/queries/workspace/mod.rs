@@ -1,3 +1,4 @@
 mod load_current_branch;
 mod load_packed_refs;
+pub(crate) mod operation_state;
 pub mod repo_status;
@@ -0,0 +1,299 @@
+use std::fs::read_to_string;
+use std::path::Path;
+
+use serde::Serialize;
+use ts_rs::TS;
+
+use crate::git::queries::wip::{read_mailbox_session, MailboxSession};
+use crate::git::store::STORE;
+use crate::server::git_request::ReqOptions;
+use crate::server::request_util::R;
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub enum RepoOperation {
+  None,
+  Merging {
+    // More than one for an octopus merge.
+    #[serde(rename = "mergeHeads")]
+    merge_heads: Vec<String>,
+  },
+  Rebasing {
+    // Started with "rebase -i". A plain rebase is false with either backend.
+    interactive: bool,
+    // 1 based.
+    step: Option<u32>,
+    total: Option<u32>,
+    // The commit being applied when it stopped.
+    #[serde(rename = "currentCommit")]
+    current_commit: Option<String>,
+    onto: Option<String>,
+    // The branch being rebased, e.g. "refs/heads/main". None if detached.
+    #[serde(rename = "headName")]
+    head_name: Option<String>,
+  },
+  CherryPicking {
+    commit: String,
+  },
+  Reverting {
+    commit: String,
+  },
+  ApplyingMailbox(MailboxSession),
+  Bisecting {
+    // What to go back to when the bisect is reset.
+    start: String,
+    bad: Option<String>,
+    good: Vec<String>,
+  },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, TS)]
+#[ts(export)]
+pub enum OperationCommand {
+  Continue,
+  Skip,
+  Abort,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct RepoOperationState {
+  pub operation: RepoOperation,
+  pub commands: Vec<OperationCommand>,
+}
+
+pub fn load_repo_operation_state(options: &ReqOptions) -> R<RepoOperationState> {
+  let repo_path = STORE.get_repo_path(&options.repo_path)?;
+  let operation = read_operation(&options.repo_path, &repo_path.git_path);
+
+  Ok(RepoOperationState {
+    commands: get_commands(&operation),
+    operation,
+  })
+}
+
+/*
+Checked in this order as some files are left around by more than one operation. E.g. a
+rebase stopped on a conflict may also have CHERRY_PICK_HEAD with older git versions.
+ */
+fn read_operation(repo_path: &str, git_path: &Path) -> RepoOperation {
+  let rebase_merge = git_path.join("rebase-merge");
+
+  if rebase_merge.is_dir() {
+    return RepoOperation::Rebasing {
+      interactive: rebase_merge.join("interactive").exists(),
+      step: read_num(&rebase_merge.join("msgnum")),
+      total: read_num(&rebase_merge.join("end")),
+      current_commit: read_trimmed(&git_path.join("REBASE_HEAD"))
+        .or_else(|| read_trimmed(&rebase_merge.join("stopped-sha"))),
+      onto: read_trimmed(&rebase_merge.join("onto")),
+      head_name: read_head_name(&rebase_merge),
+    };
+  }
+
+  let rebase_apply = git_path.join("rebase-apply");
+
+  if rebase_apply.is_dir() {
+    if let Some(session) = read_mailbox_session(repo_path) {
+      return RepoOperation::ApplyingMailbox(session);
+    }
+
+    return RepoOperation::Rebasing {
+      interactive: false,
+      step: read_num(&rebase_apply.join("next")),
+      total: read_num(&rebase_apply.join("last")),
+      current_commit: read_trimmed(&git_path.join("REBASE_HEAD"))
+        .or_else(|| read_trimmed(&rebase_apply.join("original-commit"))),
+      onto: read_trimmed(&rebase_apply.join("onto")),
+      head_name: read_head_name(&rebase_apply),
+    };
+  }
+
+  if let Some(text) = read_trimmed(&git_path.join("MERGE_HEAD")) {
+    return RepoOperation::Merging {
+      merge_heads: text.lines().map(|l| l.trim().to_string()).collect(),
+    };
+  }
+
+  if let Some(commit) = read_trimmed(&git_path.join("CHERRY_PICK_HEAD")) {
+    return RepoOperation::CherryPicking { commit };
+  }
+
+  if let Some(commit) = read_trimmed(&git_path.join("REVERT_HEAD")) {
+    return RepoOperation::Reverting { commit };
+  }
+
+  // Several commits being picked, stopped without the file above. E.g. the conflict was
+  // committed before "--continue".
+  if let Some(operation) = read_sequencer_todo(&git_path.join("sequencer").join("todo")) {
+    return operation;
+  }
+
+  if let Some(start) = read_trimmed(&git_path.join("BISECT_START")) {
+    let refs = git_path.join("refs").join("bisect");
+
+    return RepoOperation::Bisecting {
+      start,
+      bad: read_trimmed(&refs.join("bad")),
+      good: read_good_refs(&refs),
+    };
+  }
+
+  RepoOperation::None
+}
+
+fn get_commands(operation: &RepoOperation) -> Vec<OperationCommand> {
+  use OperationCommand::*;
+
+  match operation {
+    RepoOperation::None => vec![],
+    // There's nothing to skip in a merge.
+    RepoOperation::Merging { .. } => vec![Continue, Abort],
+    // Skip is "bisect skip", abort is "bisect reset".
+    RepoOperation::Bisecting { .. } => vec![Skip, Abort],
+    RepoOperation::Rebasing { .. }
+    | RepoOperation::CherryPicking { .. }
+    | RepoOperation::Reverting { .. }
+    | RepoOperation::ApplyingMailbox(_) => vec![Continue, Skip, Abort],
+  }
+}
+
+// "detached HEAD" is written when there's no branch.
+fn read_head_name(dir: &Path) -> Option<String> {
+  read_trimmed(&dir.join("head-name")).filter(|name| name != "detached HEAD")
+}
+
+// Good refs are named "good-<id>", unless they were packed.
+fn read_good_refs(refs: &Path) -> Vec<String> {
+  let Ok(entries) = std::fs::read_dir(refs) else {
+    return Vec::new();
+  };
+
+  let mut good: Vec<String> = entries
+    .filter_map(|e| e.ok())
+    .filter(|e| e.file_name().to_string_lossy().starts_with("good-"))
+    .filter_map(|e| read_trimmed(&e.path()))
+    .collect();
+
+  good.sort();
+  good
+}
+
+// The first line is the commit it stopped on, e.g. "pick a0a8cb1 Subject".
+fn read_sequencer_todo(path: &Path) -> Option<RepoOperation> {
+  let text = read_trimmed(path)?;
+  let line = text.lines().find(|l| !l.starts_with('#'))?;
+  let mut parts = line.split_whitespace();
+  let command = parts.next()?;
+  let commit = parts.next()?.to_string();
+
+  match command {
+    "pick" | "p" => Some(RepoOperation::CherryPicking { commit }),
+    "revert" => Some(RepoOperation::Reverting { commit }),
+    _ => None,
+  }
+}
+
+fn read_num(path: &Path) -> Option<u32> {
+  read_trimmed(path)?.parse().ok()
+}
+
+fn read_trimmed(path: &Path) -> Option<String> {
+  let text = read_to_string(path).ok()?.trim().to_string();
+
+  if text.is_empty() {
+    None
+  } else {
+    Some(text)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::fs;
+
+  use crate::git::queries::workspace::operation_state::{
+    get_commands, read_operation, OperationCommand, RepoOperation,
+  };
+
+  #[test]
+  fn test_read_operation() {
+    let git_path = std::env::temp_dir().join("operation_state_test");
+    let _ = fs::remove_dir_all(&git_path);
+    fs::create_dir_all(&git_path).unwrap();
+
+    assert!(matches!(read_operation("", &git_path), RepoOperation::None));
+
+    fs::write(git_path.join("MERGE_HEAD"), "aaa\nbbb\n").unwrap();
+    fs::write(git_path.join("CHERRY_PICK_HEAD"), "ccc\n").unwrap();
+
+    let merging = read_operation("", &git_path);
+    assert!(
+      matches!(&merging, RepoOperation::Merging { merge_heads } if merge_heads == &["aaa", "bbb"])
+    );
+    assert_eq!(
+      get_commands(&merging),
+      vec![OperationCommand::Continue, OperationCommand::Abort]
+    );
+
+    let rebase = git_path.join("rebase-merge");
+    fs::create_dir_all(&rebase).unwrap();
+    fs::write(rebase.join("interactive"), "").unwrap();
+    fs::write(rebase.join("msgnum"), "2\n").unwrap();
+    fs::write(rebase.join("end"), "5\n").unwrap();
+    fs::write(rebase.join("onto"), "ddd\n").unwrap();
+    fs::write(rebase.join("head-name"), "detached HEAD\n").unwrap();
+    fs::write(git_path.join("REBASE_HEAD"), "eee\n").unwrap();
+
+    match read_operation("", &git_path) {
+      RepoOperation::Rebasing {
+        interactive,
+        step,
+        total,
+        current_commit,
+        onto,
+        head_name,
+      } => {
+        assert!(interactive);
+        assert_eq!((step, total), (Some(2), Some(5)));
+        assert_eq!(current_commit.as_deref(), Some("eee"));
+        assert_eq!(onto.as_deref(), Some("ddd"));
+        assert_eq!(head_name, None);
+      }
+      other => panic!("Expected rebase, got {:?}", other),
+    }
+
+    fs::remove_dir_all(&git_path).unwrap();
+  }
+
+  #[test]
+  fn test_read_sequencer_operation() {
+    let git_path = std::env::temp_dir().join("operation_state_sequencer_test");
+    let _ = fs::remove_dir_all(&git_path);
+    let sequencer = git_path.join("sequencer");
+    fs::create_dir_all(&sequencer).unwrap();
+
+    fs::write(sequencer.join("todo"), "pick a0a8cb1 B\npick ce1b7d7 C\n").unwrap();
+    assert!(matches!(
+      read_operation("", &git_path),
+      RepoOperation::CherryPicking { commit } if commit == "a0a8cb1"
+    ));
+
+    fs::write(sequencer.join("todo"), "revert ce1b7d7 C\n").unwrap();
+    assert!(matches!(
+      read_operation("", &git_path),
+      RepoOperation::Reverting { commit } if commit == "ce1b7d7"
+    ));
+
+    // The head file is read first when there is one.
+    fs::write(git_path.join("REVERT_HEAD"), "fff\n").unwrap();
+    assert!(matches!(
+      read_operation("", &git_path),
+      RepoOperation::Reverting { commit } if commit == "fff"
+    ));
+
+    fs::remove_dir_all(&git_path).unwrap();
+  }
+}
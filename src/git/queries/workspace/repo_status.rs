@@ -1,11 +1,15 @@
 use crate::git::queries::commit_calcs::count_commits_between_fallback;
 use crate::git::queries::config::load_full_config;
 use crate::git::queries::config::GitConfig;
+use crate::git::queries::syntax_colouring::clear_linguist_languages;
 use crate::git::queries::wip::wip_patches::{load_wip_patches, WipPatches};
 use crate::git::queries::workspace::load_current_branch::{
   load_current_branch, read_refs, Refs,
 };
 use crate::git::queries::workspace::load_packed_refs::{load_packed_refs, PackedRef};
+use crate::git::queries::workspace::operation_state::{
+  load_repo_operation_state, RepoOperationState,
+};
 use crate::server::git_request::ReqOptions;
 use crate::server::request_util::R;
 use serde::Serialize;
@@ -27,6 +31,8 @@ pub struct RepoStatus {
   remote_ahead: u32,
   remote_behind: u32,
   state: BranchState,
+  // Merge, rebase, "git am" etc. that's stopped, and what can be done about it.
+  operation: RepoOperationState,
 }
 
 #[derive(Debug, Serialize, TS)]
@@ -41,7 +47,9 @@ pub fn load_repo_status(options: &ReqOptions) -> R<RepoStatus> {
   let ReqOptions { repo_path } = options;
 
   let patches = load_wip_patches(options)?;
+  let operation = load_repo_operation_state(options)?;
   let config = load_full_config(options)?;
+  clear_linguist_languages(repo_path);
 
   let (head_id, current_branch) = load_current_branch(repo_path)?;
 
@@ -98,6 +106,7 @@ pub fn load_repo_status(options: &ReqOptions) -> R<RepoStatus> {
         remote_ahead,
         remote_behind,
         state: BranchState::Both,
+        operation,
       });
     }
   }
@@ -126,5 +135,6 @@ pub fn load_repo_status(options: &ReqOptions) -> R<RepoStatus> {
     remote_ahead: 0,
     remote_behind: 0,
     state,
+    operation,
   })
 }
@@ -1,11 +1,13 @@
 use crate::dprintln;
-use chardetng::EncodingDetector;
 use std::ffi::OsStr;
+use std::io::Write;
 use std::path::Path;
-use std::process::{Command, Output};
+use std::process::{Command, Output, Stdio};
+use std::thread;
 
 use crate::git::git_settings::GIT_PATH;
-use crate::server::request_util::R;
+use crate::server::request_util::{ES, R};
+use crate::util::text_encoding::decode_text;
 
 #[derive(Clone, Debug)]
 pub struct RunGitOptions<'a, I, S>
@@ -41,13 +43,41 @@ where
   })
 }
 
-fn read_buffer_to_string(bytes: &[u8]) -> String {
-  let mut decoder = EncodingDetector::new();
-  decoder.feed(bytes, true);
-  let encoding = decoder.guess(None, true);
-  let content = encoding.decode(bytes).0;
+// Like run_git_err, with input written to stdin for options like "--stdin".
+pub fn run_git_stdin<I, S>(options: RunGitOptions<I, S>, input: Vec<u8>) -> R<GitOut>
+where
+  I: IntoIterator<Item = S>,
+  S: AsRef<OsStr>,
+{
+  let mut child = Command::new(Path::new(GIT_PATH.as_path()))
+    .args(options.args)
+    .current_dir(options.repo_path)
+    .stdin(Stdio::piped())
+    .stdout(Stdio::piped())
+    .stderr(Stdio::piped())
+    .spawn()?;
+
+  let mut stdin = child
+    .stdin
+    .take()
+    .ok_or_else(|| ES::from("Failed to open stdin for git"))?;
+
+  // Written on another thread, as git could block on a full stdout before reading it all.
+  let writer = thread::spawn(move || stdin.write_all(&input));
+
+  let out = child.wait_with_output()?;
+  let _ = writer.join();
+
+  let Output { stdout, stderr, .. } = &out;
 
-  content.into_owned()
+  Ok(GitOut {
+    stdout: read_buffer_to_string(stdout),
+    stderr: read_buffer_to_string(stderr),
+  })
+}
+
+fn read_buffer_to_string(bytes: &[u8]) -> String {
+  decode_text(bytes, None).text
 }
 
 pub fn run_git_buffer<I, S>(options: RunGitOptions<I, S>) -> Option<Vec<u8>>
@@ -91,6 +121,23 @@ mod tests {
     assert!(!text.unwrap().stdout.is_empty());
   }
 
+  #[test]
+  fn test_run_git_stdin() {
+    let out = run_git::run_git_stdin(
+      RunGitOptions {
+        args: ["hash-object", "--stdin"],
+        repo_path: ".",
+      },
+      b"a\n".to_vec(),
+    )
+    .unwrap();
+
+    assert_eq!(
+      out.stdout.trim(),
+      "78981922613b2afb6025042ff6bd878ac1994e85"
+    );
+  }
+
   #[test]
   fn test_git_path() {
     let p = Path::new("git");
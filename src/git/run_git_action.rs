@@ -24,6 +24,8 @@ use crate::server::request_util::{ES, R};
 pub enum ActionError {
   Credential,
   Git,
+  // Name of the hook that rejected the action.
+  Hook(String),
   IO(String),
 }
 
@@ -33,6 +35,14 @@ impl From<Error> for ActionError {
   }
 }
 
+impl From<ES> for ActionError {
+  fn from(err: ES) -> Self {
+    match err {
+      ES::Text(text) => IO(text),
+    }
+  }
+}
+
 #[derive(Clone, Debug)]
 pub struct RunGitActionOptions<'a, const N: usize> {
   pub commands: [Vec<&'a str>; N],
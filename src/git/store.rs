@@ -32,6 +32,14 @@ static PATCHES: Glo<(PathString, HashMap<PatchPath, Vec<Patch>>)> =
 pub static REF_DIFFS: Glo<AHashMap<String, u32>> = glo!(AHashMap::new());
 // This probably needs to be per repo. We could then watch for changes?
 pub static CONFIG: Global<AHashMap<PathString, GitConfig>> = global!(AHashMap::new());
+// Values we had to ask git for as they weren't in CONFIG, including ones that aren't set.
+// Key is the repo path and lower case config key. Cleared with the repo's CONFIG.
+pub static CONFIG_FALLBACKS: Global<AHashMap<(PathString, String), Option<String>>> =
+  global!(AHashMap::new());
+// linguist-language for each repo and file path, so it isn't asked for on every render.
+// Cleared when the repo status is loaded, in case .gitattributes changed.
+pub static LINGUIST_LANGUAGES: Global<AHashMap<(PathString, String), Option<String>>> =
+  global!(AHashMap::new());
 pub static GIT_VERSION: Glo<GitVersion> = glo!(GitVersion::new());
 
 pub const STORE: Store = Store {};
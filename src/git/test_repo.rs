@@ -0,0 +1,64 @@
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+// A throwaway repo for tests that need real git objects. Removed when dropped.
+pub struct TestRepo {
+  pub path: PathBuf,
+}
+
+impl TestRepo {
+  // name must be unique between tests, as they run in parallel.
+  pub fn new(name: &str) -> Self {
+    let path = std::env::temp_dir().join(format!("gitfiend_test_{}", name));
+    let _ = fs::remove_dir_all(&path);
+    fs::create_dir_all(&path).unwrap();
+
+    let repo = Self { path };
+    repo.git(&["init", "-q", "-b", "main"]);
+    repo.git(&["config", "user.name", "Test"]);
+    repo.git(&["config", "user.email", "test@example.com"]);
+    repo.git(&["config", "core.autocrlf", "false"]);
+
+    repo
+  }
+
+  pub fn path_str(&self) -> String {
+    self.path.to_string_lossy().to_string()
+  }
+
+  pub fn write(&self, file: &str, content: impl AsRef<[u8]>) {
+    fs::write(self.path.join(file), content).unwrap();
+  }
+
+  // Returns trimmed stdout. Panics if git fails.
+  pub fn git(&self, args: &[&str]) -> String {
+    let out = Command::new("git")
+      .args(args)
+      .current_dir(&self.path)
+      .output()
+      .unwrap();
+
+    assert!(
+      out.status.success(),
+      "git {:?} failed: {}",
+      args,
+      String::from_utf8_lossy(&out.stderr)
+    );
+
+    String::from_utf8_lossy(&out.stdout).trim().to_string()
+  }
+
+  // Stages everything and commits. Returns the new commit id.
+  pub fn commit(&self, message: &str) -> String {
+    self.git(&["add", "-A"]);
+    self.git(&["commit", "-q", "-m", message]);
+    self.git(&["rev-parse", "HEAD"])
+  }
+}
+
+impl Drop for TestRepo {
+  fn drop(&mut self) {
+    let _ = fs::remove_dir_all(&self.path);
+  }
+}
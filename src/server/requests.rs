@@ -3,33 +3,59 @@ use std::process::exit;
 use tiny_http::{Response, Server};
 
 use crate::git::actions::add::git_add_files;
+use crate::git::actions::apply_patch::{
+  abort_mailbox, apply_mailbox, apply_patch, check_patch, continue_mailbox,
+  skip_mailbox_patch,
+};
 use crate::git::actions::clone::clone_repo;
 use crate::git::actions::command::command;
+use crate::git::actions::commit::{create_commit, load_commit_template};
 use crate::git::actions::create_repo::create_repo;
 use crate::git::actions::credentials::set_credentials;
+use crate::git::actions::export_patches::export_patches;
 use crate::git::actions::fetch::fetch_all;
 use crate::git::actions::stash::{stash_changes, stash_staged};
+use crate::git::actions::tags::{create_tag, delete_tag, push_tags};
 use crate::git::conflicts::api::load_conflicted_file;
+use crate::git::conflicts::auto_resolve::auto_resolve_conflicts;
+use crate::git::conflicts::resolve::resolve_conflicted_file;
 use crate::git::git_version::git_version;
+use crate::git::queries::blame::{get_blame_as_html, load_blame};
 use crate::git::queries::commits::{
   commit_ids_between_commits, commit_is_ancestor, commit_is_on_branch,
   get_all_commits_on_current_branch, load_commits_and_refs,
 };
-use crate::git::queries::hunks::html_code::get_patch_as_html;
+use crate::git::queries::file_history::load_file_history;
+use crate::git::queries::hunks::code_tokens::{get_patch_tokens, get_wip_patch_tokens};
+use crate::git::queries::hunks::expand_context::expand_hunk_context;
+use crate::git::queries::hunks::html_code::{
+  get_diff_between_as_html, get_patch_as_html, get_patch_as_html_page,
+  get_wip_patch_as_html, get_wip_patch_as_html_page,
+};
 use crate::git::queries::hunks::images::load_commit_image;
-use crate::git::queries::hunks::load_hunks::{load_hunks, load_hunks_split};
+use crate::git::queries::hunks::line_history::load_line_history;
+use crate::git::queries::hunks::load_hunks::{
+  load_commit_hunks, load_hunks, load_hunks_between, load_hunks_between_split,
+  load_hunks_split,
+};
+use crate::git::queries::patches::patches_between::load_patches_between;
 use crate::git::queries::patches::patches_for_commit::load_patches_for_commit;
 use crate::git::queries::refs::ref_diffs::calc_ref_diffs;
 use crate::git::queries::run::run;
 use crate::git::queries::scan_workspace::scan_workspace;
 use crate::git::queries::search::search_commits::search_commits;
 use crate::git::queries::search::search_request::{poll_diff_search, start_diff_search};
+use crate::git::queries::syntax_colouring::{
+  get_syntax_stylesheet, get_syntax_themes, set_syntax_theme,
+};
+use crate::git::queries::tags::{describe_commit, load_tag_details};
 use crate::git::queries::unpushed_commits::get_un_pushed_commits;
 use crate::git::queries::wip::is_rebase_in_progress;
 use crate::git::queries::wip::wip_diff::{
   load_wip_hunk_lines, load_wip_hunks, load_wip_hunks_split,
 };
 use crate::git::queries::wip::wip_patches::load_wip_patches;
+use crate::git::queries::workspace::operation_state::load_repo_operation_state;
 use crate::git::queries::workspace::repo_status::load_repo_status;
 use crate::git::run_git_action::poll_action2;
 use crate::git::store::{clear_all_caches, clear_cache, override_git_home};
@@ -80,20 +106,39 @@ pub fn start_async_server() {
 
           scan_workspace,
           load_repo_status,
+          load_repo_operation_state,
 
           is_rebase_in_progress,
           load_commits_and_refs,
 
           load_hunks,
           load_hunks_split,
+          load_hunks_between,
+          load_hunks_between_split,
+          load_commit_hunks,
+          expand_hunk_context,
           load_wip_hunks,
           load_wip_hunk_lines,
           load_wip_hunks_split,
           load_conflicted_file,
+          load_commit_template,
+          check_patch,
+          export_patches,
           get_patch_as_html,
+          get_patch_as_html_page,
+          get_wip_patch_as_html,
+          get_wip_patch_as_html_page,
+          get_patch_tokens,
+          get_wip_patch_tokens,
+          get_diff_between_as_html,
+          load_blame,
+          get_blame_as_html,
+          get_syntax_themes,
+          get_syntax_stylesheet,
 
           load_wip_patches,
           load_patches_for_commit,
+          load_patches_between,
           load_commit_image,
 
           commit_ids_between_commits,
@@ -102,6 +147,10 @@ pub fn start_async_server() {
           commit_is_ancestor,
           commit_is_on_branch,
           get_all_commits_on_current_branch,
+          load_tag_details,
+          describe_commit,
+          load_file_history,
+          load_line_history,
 
           search_commits,
           start_diff_search,
@@ -122,15 +171,27 @@ pub fn start_async_server() {
           override_git_home,
           get_data_store,
           set_data_store,
+          set_syntax_theme,
 
           // Actions
           command,
+          create_commit,
           git_add_files,
           stash_changes,
           fetch_all,
           clone_repo,
           create_repo,
-          stash_staged
+          stash_staged,
+          create_tag,
+          delete_tag,
+          push_tags,
+          apply_patch,
+          apply_mailbox,
+          continue_mailbox,
+          skip_mailbox_patch,
+          abort_mailbox,
+          resolve_conflicted_file,
+          auto_resolve_conflicts
         }
       }
       _ => {
@@ -2,6 +2,7 @@ pub(crate) mod data_store;
 pub(crate) mod debug_print;
 pub(crate) mod global;
 pub(crate) mod short_cache;
+pub(crate) mod text_encoding;
 
 #[macro_export]
 macro_rules! f {
@@ -0,0 +1,79 @@
+use chardetng::EncodingDetector;
+use encoding_rs::{Encoding, UTF_8};
+
+pub struct DecodedText {
+  // Without the BOM.
+  pub text: String,
+  pub encoding: &'static Encoding,
+  pub bom: bool,
+}
+
+/*
+For text we weren't told the encoding of. A BOM wins, then UTF-8 if it's valid. Otherwise
+we use the fallback if there is one, or chardetng's guess.
+ */
+pub fn decode_text(bytes: &[u8], fallback: Option<&'static Encoding>) -> DecodedText {
+  if let Some((encoding, bom_len)) = Encoding::for_bom(bytes) {
+    return DecodedText {
+      text: encoding
+        .decode_without_bom_handling(&bytes[bom_len..])
+        .0
+        .into_owned(),
+      encoding,
+      bom: true,
+    };
+  }
+
+  if let Ok(text) = std::str::from_utf8(bytes) {
+    return DecodedText {
+      text: text.to_string(),
+      encoding: UTF_8,
+      bom: false,
+    };
+  }
+
+  let encoding = fallback.unwrap_or_else(|| guess_encoding(bytes));
+
+  DecodedText {
+    text: encoding.decode_without_bom_handling(bytes).0.into_owned(),
+    encoding,
+    bom: false,
+  }
+}
+
+fn guess_encoding(bytes: &[u8]) -> &'static Encoding {
+  let mut detector = EncodingDetector::new();
+  detector.feed(bytes, true);
+
+  detector.guess(None, true)
+}
+
+#[cfg(test)]
+mod tests {
+  use encoding_rs::{UTF_16LE, UTF_8, WINDOWS_1252};
+
+  use crate::util::text_encoding::decode_text;
+
+  #[test]
+  fn test_decode_text() {
+    let utf8 = decode_text(b"\xEF\xBB\xBFcaf\xC3\xA9\n", None);
+    assert_eq!(
+      (utf8.text.as_str(), utf8.encoding, utf8.bom),
+      ("café\n", UTF_8, true)
+    );
+
+    let utf16 = decode_text(b"\xFF\xFEa\x00\n\x00", None);
+    assert_eq!(
+      (utf16.text.as_str(), utf16.encoding, utf16.bom),
+      ("a\n", UTF_16LE, true)
+    );
+
+    let guessed = decode_text(b"caf\xE9 cr\xE8me br\xFBl\xE9e\n", None);
+    assert_eq!(guessed.text, "café crème brûlée\n");
+    assert!(!guessed.bom);
+
+    // Valid UTF-8 doesn't use the fallback.
+    assert_eq!(decode_text(b"a\n", Some(WINDOWS_1252)).encoding, UTF_8);
+    assert_eq!(decode_text(b"\xE9\n", Some(WINDOWS_1252)).text, "é\n");
+  }
+}